@@ -0,0 +1,50 @@
+//! Run with `RUST_BACKTRACE=0`, otherwise capturing the backtrace dominates the timings.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use typederror::prelude::*;
+
+#[derive(Debug, thiserror::Error)]
+enum MyError {
+    #[error("not found")]
+    NotFound,
+}
+
+fn fail() -> std::result::Result<(), MyError> {
+    Err(MyError::NotFound)
+}
+
+fn bench_context(c: &mut Criterion) {
+    let mut group = c.benchmark_group("context");
+
+    // Static string context is stored as-is, without allocating a `String`.
+    group.bench_function("static_str", |b| {
+        b.iter(|| {
+            let err: TError<MyError> = fail().context(black_box("loading config")).unwrap_err();
+            black_box(err)
+        })
+    });
+
+    // The previous behaviour, where the context was always converted to a `String` first.
+    group.bench_function("to_string", |b| {
+        b.iter(|| {
+            let err: TError<MyError> = fail()
+                .context(black_box("loading config").to_string())
+                .unwrap_err();
+            black_box(err)
+        })
+    });
+
+    group.bench_function("terror_context", |b| {
+        b.iter(|| {
+            let err = TError::new(MyError::NotFound).context(black_box("loading config"));
+            black_box(err)
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_context);
+criterion_main!(benches);
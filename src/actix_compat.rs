@@ -0,0 +1,61 @@
+use core::fmt::{Debug, Display};
+
+use actix_web::http::StatusCode;
+use actix_web::ResponseError;
+
+use crate::{HttpStatus, TError};
+
+/// Allow a `TError` to be returned from an `actix-web` handler.
+///
+/// The status code of the response is taken from the primary error, and the
+/// body is the top-level error message. If the primary error is not present,
+/// or its status code is not valid, the status code is 500.
+impl<E> ResponseError for TError<E>
+where
+    E: HttpStatus + Debug + Display + Send + Sync + 'static,
+{
+    fn status_code(&self) -> StatusCode {
+        TError::status_code(self)
+            .and_then(|code| StatusCode::from_u16(code).ok())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::body::MessageBody;
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+
+    use crate::{HttpStatus, TError};
+
+    #[derive(Debug, thiserror::Error)]
+    enum ApiError {
+        #[error("not found")]
+        NotFound,
+    }
+
+    impl HttpStatus for ApiError {
+        fn status_code(&self) -> u16 {
+            match self {
+                ApiError::NotFound => 404,
+            }
+        }
+    }
+
+    #[test]
+    fn test_response_error() {
+        let err = TError::new(ApiError::NotFound).context("user 42");
+        assert_eq!(ResponseError::status_code(&err), StatusCode::NOT_FOUND);
+        let response = err.error_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = response.into_body().try_into_bytes().unwrap();
+        assert_eq!(body, "user 42");
+
+        let err: TError<ApiError> = TError::from_msg("database unavailable");
+        assert_eq!(
+            ResponseError::status_code(&err),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
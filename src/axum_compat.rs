@@ -0,0 +1,92 @@
+use core::fmt::{Debug, Display};
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+
+use crate::{HttpStatus, TError};
+
+/// Allow a `TError` to be returned from an `axum` handler.
+///
+/// The status code of the response is taken from the primary error, and the
+/// body is `{ "error": "<message>" }`, using the top-level error message. If
+/// the primary error is not present, or its status code is not valid, the
+/// status code is 500.
+///
+/// The `ErrorCode` of the primary error is not included, since that would
+/// require every primary error type to implement `ErrorCode`.
+impl<E> IntoResponse for TError<E>
+where
+    E: HttpStatus + Debug + Display + Send + Sync + 'static,
+{
+    fn into_response(self) -> Response {
+        let status = self
+            .status_code()
+            .and_then(|code| StatusCode::from_u16(code).ok())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let body = serde_json::json!({ "error": self.to_string() });
+        (status, Json(body)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+
+    use crate::{HttpStatus, TError};
+
+    #[derive(Debug, thiserror::Error)]
+    enum ApiError {
+        #[error("not found")]
+        NotFound,
+    }
+
+    impl HttpStatus for ApiError {
+        fn status_code(&self) -> u16 {
+            match self {
+                ApiError::NotFound => 404,
+            }
+        }
+    }
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    /// Read the body of a response, which is always ready for a JSON body.
+    fn body_json(response: axum::response::Response) -> serde_json::Value {
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+        let mut fut = Box::pin(axum::body::to_bytes(response.into_body(), usize::MAX));
+        match fut.as_mut().poll(&mut cx) {
+            Poll::Ready(bytes) => serde_json::from_slice(&bytes.unwrap()).unwrap(),
+            Poll::Pending => panic!("body not ready"),
+        }
+    }
+
+    #[test]
+    fn test_into_response() {
+        let err = TError::new(ApiError::NotFound).context("user 42");
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            body_json(response),
+            serde_json::json!({ "error": "user 42" })
+        );
+
+        let err: TError<ApiError> = TError::from_msg("database unavailable");
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            body_json(response),
+            serde_json::json!({ "error": "database unavailable" })
+        );
+    }
+}
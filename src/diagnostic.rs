@@ -0,0 +1,139 @@
+use core::fmt::{Debug, Display};
+use std::error::Error as StdError;
+
+use miette::{Diagnostic, LabeledSpan, Severity, SourceCode};
+
+use crate::TError;
+
+/// A `TError` that implements `miette::Diagnostic`.
+///
+/// If the primary error is present, its diagnostic code, help, url, labels
+/// and so on are forwarded. Otherwise the error chain is reported as a plain
+/// diagnostic with no code.
+///
+/// Create one with `TError::into_diagnostic()`.
+pub struct TDiagnostic<E>(TError<E>);
+
+impl<E> TDiagnostic<E> {
+    /// Get the underlying `TError`.
+    pub fn into_inner(self) -> TError<E> {
+        self.0
+    }
+}
+
+impl<E> Debug for TDiagnostic<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl<E> Display for TDiagnostic<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl<E> StdError for TDiagnostic<E> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.0.source()
+    }
+}
+
+impl<E: Diagnostic + Send + Sync + 'static> Diagnostic for TDiagnostic<E> {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.0.get_ref()?.code()
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        self.0.get_ref()?.severity()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.0.get_ref()?.help()
+    }
+
+    fn url<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.0.get_ref()?.url()
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.0.get_ref()?.source_code()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.0.get_ref()?.labels()
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        self.0.get_ref()?.related()
+    }
+
+    fn diagnostic_source(&self) -> Option<&dyn Diagnostic> {
+        self.0.get_ref()?.diagnostic_source()
+    }
+}
+
+/// Convert a `TError` into a `miette::Report`.
+///
+/// This allows `?` to be used on a `TError` in a function returning
+/// `miette::Result`.
+impl<E: Diagnostic + Send + Sync + 'static> From<TError<E>> for miette::Report {
+    fn from(err: TError<E>) -> Self {
+        miette::Report::new(err.into_diagnostic())
+    }
+}
+
+impl<E: Diagnostic + Send + Sync + 'static> TError<E> {
+    /// Convert this error into a `TDiagnostic`, which implements
+    /// `miette::Diagnostic`.
+    pub fn into_diagnostic(self) -> TDiagnostic<E> {
+        TDiagnostic(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::Display;
+
+    use miette::Diagnostic;
+
+    use crate::TError;
+
+    #[derive(Debug, thiserror::Error)]
+    enum MyError {
+        #[error("not found")]
+        NotFound,
+    }
+
+    impl Diagnostic for MyError {
+        fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+            Some(Box::new("my_error::not_found"))
+        }
+
+        fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+            Some(Box::new("check the name"))
+        }
+    }
+
+    #[test]
+    fn test_into_diagnostic() {
+        let diag = TError::new(MyError::NotFound)
+            .context("lookup failed")
+            .into_diagnostic();
+        assert_eq!(diag.to_string(), "lookup failed");
+        assert_eq!(diag.code().unwrap().to_string(), "my_error::not_found");
+        assert_eq!(diag.help().unwrap().to_string(), "check the name");
+        assert_eq!(
+            std::error::Error::source(&diag).unwrap().to_string(),
+            "not found"
+        );
+
+        let diag = TError::<MyError>::from_msg("other").into_diagnostic();
+        assert_eq!(diag.to_string(), "other");
+        assert!(diag.code().is_none());
+        assert!(diag.help().is_none());
+
+        let report = miette::Report::from(TError::new(MyError::NotFound));
+        assert_eq!(report.code().unwrap().to_string(), "my_error::not_found");
+    }
+}
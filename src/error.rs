@@ -1,19 +1,45 @@
-use std::fmt::Display;
-use std::{error::Error as StdError, fmt::Debug};
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Display;
+use core::panic::Location;
+use core::{error::Error as StdError, fmt::Debug};
+
+use crate::multi::MultiError;
 
 #[derive(Debug)]
 pub struct SimpleError(pub String);
 
 impl Display for SimpleError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         Display::fmt(&self.0, f)
     }
 }
 
 impl StdError for SimpleError {}
 
+/// Wrapper that stores context in an `anyhow::Error` by its `Display` output only.
+///
+/// The context cannot be downcast to its original type, which keeps context
+/// display-only without first allocating a `String` for it.
+struct DisplayOnly<C>(C);
+
+impl<C: Display> Debug for DisplayOnly<C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // Match the `Debug` output of the equivalent `String`.
+        Debug::fmt(&self.0.to_string(), f)
+    }
+}
+
+impl<C: Display> Display for DisplayOnly<C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
 /// Convenience type alias for `Result<T, TError<E>>`.
-pub type Result<T, E = SimpleError> = std::result::Result<T, TError<E>>;
+pub type Result<T, E = SimpleError> = core::result::Result<T, TError<E>>;
 
 /// A wrapper around `anyhow::Error` that allows for downcasting to a specific error type.
 ///
@@ -22,300 +48,2512 @@ pub type Result<T, E = SimpleError> = std::result::Result<T, TError<E>>;
 /// parameter acts as documentation for the returned error type for
 /// the caller to match on, while the underlying anyhow::Error also
 /// allows for other errors to be captured along with any context.
+///
+/// `TError` is `#[must_use]`, so methods that return a new `TError`, such as
+/// `context()` and `change_err()`, warn if the result is not used.
+#[must_use]
 pub struct TError<E = SimpleError> {
-    phantom: std::marker::PhantomData<E>,
+    /// E is only a marker, so use `fn() -> E` to keep `TError` `Send` and
+    /// `Sync` regardless of whether E is.
+    phantom: core::marker::PhantomData<fn() -> E>,
     error: anyhow::Error,
+    location: CallerLocation,
+    /// The number of context layers added via `TError`, from the top of the chain.
+    contexts: usize,
+    /// Rarely used details, allocated on first use to keep `TError` small.
+    extra: Option<Box<Extra>>,
 }
 
-impl<E> Debug for TError<E> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        Debug::fmt(&self.error, f)
-    }
+/// The details of a `TError` that most errors do not need.
+#[derive(Clone)]
+struct Extra {
+    /// The location of each context layer added via `TError`, most recent last.
+    #[cfg(feature = "location")]
+    context_locations: Vec<&'static Location<'static>>,
+    /// Trailing notes, displayed after the error. See `TError::note()`.
+    notes: Vec<String>,
+    meta: Meta,
 }
 
-impl<E> Display for TError<E> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        Display::fmt(&self.error, f)
+impl Extra {
+    fn new() -> Self {
+        Self {
+            #[cfg(feature = "location")]
+            context_locations: Vec::new(),
+            notes: Vec::new(),
+            meta: Meta::capture(),
+        }
     }
-}
 
-impl<T> From<TError<T>> for anyhow::Error {
-    fn from(err: TError<T>) -> Self {
-        err.error
+    /// The details for a new error. Nothing is allocated unless the
+    /// `capture-meta` feature is enabled.
+    fn capture() -> Option<Box<Extra>> {
+        if cfg!(feature = "capture-meta") {
+            Some(Box::new(Extra::new()))
+        } else {
+            None
+        }
     }
-}
 
-impl<T> From<TError<T>> for Box<dyn StdError> {
-    fn from(err: TError<T>) -> Self {
-        err.error.into()
+    /// The details for a new error with a single context layer added at `location`.
+    #[cfg_attr(not(feature = "location"), allow(unused_variables))]
+    fn for_context(location: CallerLocation) -> Option<Box<Extra>> {
+        #[cfg(feature = "location")]
+        return Some(Box::new(Extra {
+            context_locations: alloc::vec![location.location],
+            ..Extra::new()
+        }));
+        #[cfg(not(feature = "location"))]
+        Extra::capture()
     }
-}
 
-impl<E: Debug + Display + Send + Sync + 'static> TError<E> {
-    pub fn from_anyhow(error: anyhow::Error) -> Self {
-        Self {
-            phantom: std::marker::PhantomData,
-            error,
+    /// Keep only the metadata, for a new error derived from this one.
+    fn meta_only(&self) -> Option<Box<Extra>> {
+        if cfg!(feature = "capture-meta") {
+            Some(Box::new(Extra {
+                #[cfg(feature = "location")]
+                context_locations: Vec::new(),
+                notes: Vec::new(),
+                meta: self.meta.clone(),
+            }))
+        } else {
+            None
         }
     }
 
-    pub fn from_msg(msg: &str) -> Self {
+    /// Forget the context layer locations, once the layers are no longer
+    /// at the top of the chain.
+    fn without_contexts(extra: Option<Box<Extra>>) -> Option<Box<Extra>> {
+        #[cfg(feature = "location")]
+        let extra = extra.map(|mut x| {
+            x.context_locations.clear();
+            x
+        });
+        extra
+    }
+}
+
+/// When and on which thread a `TError` was created, if the `capture-meta`
+/// feature is enabled. Otherwise this is empty.
+#[derive(Clone)]
+struct Meta {
+    #[cfg(feature = "capture-meta")]
+    created_at: std::time::SystemTime,
+    #[cfg(feature = "capture-meta")]
+    thread: std::thread::Thread,
+}
+
+impl Meta {
+    fn capture() -> Self {
         Self {
-            phantom: std::marker::PhantomData,
-            error: anyhow::anyhow!("{}", msg),
+            #[cfg(feature = "capture-meta")]
+            created_at: std::time::SystemTime::now(),
+            #[cfg(feature = "capture-meta")]
+            thread: std::thread::current(),
         }
     }
+}
 
-    /// Get the most recent error of the default type E.
-    pub fn try_get(self) -> Result<E, TError<E>> {
-        self.error.downcast().map_err(|e| TError {
-            phantom: std::marker::PhantomData,
-            error: e,
-        })
-    }
+// `TError` must stay `Send + Sync` so it can be held across `.await` points
+// and sent between threads.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+    assert_send_sync::<TError>();
+    assert_send_sync::<TError<()>>();
+    assert_send_sync::<TError<*const ()>>();
+    assert_send_sync::<crate::Result<(), MultiError>>();
+};
 
-    /// Get the most recent error of the default type E.
-    pub fn get_ref(&self) -> Option<&E> {
-        self.error.downcast_ref::<E>()
+/// The source location where a `TError` was created.
+///
+/// This is only recorded when the `location` feature is enabled, and is
+/// zero-sized otherwise.
+#[derive(Clone, Copy)]
+struct CallerLocation {
+    #[cfg(feature = "location")]
+    location: &'static Location<'static>,
+}
+
+/// The location of the caller, if the `location` feature is enabled.
+#[track_caller]
+fn caller_location() -> CallerLocation {
+    CallerLocation {
+        #[cfg(feature = "location")]
+        location: Location::caller(),
     }
+}
 
-    /// Get the most recent error of type T.
-    pub fn downcast_ref<T: Debug + Display + Send + Sync + 'static>(&self) -> Option<&T> {
-        self.error.downcast_ref::<T>()
+impl<E> Debug for TError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(&self.error, f)?;
+        for note in self.notes() {
+            write!(f, "\n\nNote: {note}")?;
+        }
+        Ok(())
     }
+}
 
-    pub fn downcast<T: Debug + Display + Send + Sync + 'static>(self) -> Result<T, Self> {
-        self.error.downcast::<T>().map_err(|e| TError {
-            phantom: std::marker::PhantomData,
-            error: e,
-        })
+impl<E> Display for TError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&self.error, f)?;
+        for note in self.notes() {
+            write!(f, " {note}")?;
+        }
+        Ok(())
     }
+}
 
-    /// Add context to the error.
-    pub fn context<C>(self, context: C) -> TError<E>
-    where
-        C: Display + Send + Sync + 'static,
-    {
-        let error = self.error.context(context);
+/// The index of the link in `chain` that holds the value at `target`,
+/// such as an error returned by `anyhow::Error::downcast_ref()`.
+///
+/// An error added as a context layer is not the link itself, but is stored
+/// inside it, so the link is found by address rather than by type.
+fn chain_position(chain: &[&(dyn StdError + 'static)], target: *const ()) -> Option<usize> {
+    let target = target as usize;
+    chain.iter().position(|link| {
+        let start = *link as *const dyn StdError as *const () as usize;
+        (start..=start + core::mem::size_of_val(*link)).contains(&target)
+    })
+}
+
+/// Split the most recent error of type E out of `error`, for `TError::into_parts()`.
+///
+/// The rest of the chain is rebuilt from plain messages, or from the message
+/// of the primary error if there is nothing else. The primary error is found
+/// by address, so a context message with the same text is not mistaken for it.
+fn split_primary<E>(error: anyhow::Error) -> core::result::Result<(E, anyhow::Error), anyhow::Error>
+where
+    E: Debug + Display + Send + Sync + 'static,
+{
+    let Some(primary) = error.downcast_ref::<E>() else {
+        return Err(error);
+    };
+    let message = primary.to_string();
+    let mut rest: Vec<String> = {
+        let chain: Vec<&(dyn StdError + 'static)> = error.chain().collect();
+        let index = chain_position(&chain, primary as *const E as *const ());
+        chain
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| Some(*i) != index)
+            .map(|(_, e)| e.to_string())
+            .collect()
+    };
+    let e = error.downcast::<E>()?;
+    let rest = match rest.pop() {
+        Some(root) => rest
+            .into_iter()
+            .rev()
+            .fold(anyhow::Error::msg(root), |error, msg| error.context(msg)),
+        None => anyhow::Error::msg(message),
+    };
+    Ok((e, rest))
+}
+
+/// Cloning a `TError` is lossy, because `anyhow::Error` is not `Clone`.
+///
+/// If the primary error of type E is present, it is cloned and the rest of
+/// the chain is re-applied around it as plain messages. Otherwise the whole
+/// chain is flattened into messages. Either way, downcasting to any other
+/// error type will fail on the clone. The `Display` output is preserved,
+/// but the backtrace is not.
+impl<E: Clone + StdError + Send + Sync + 'static> Clone for TError<E> {
+    fn clone(&self) -> Self {
+        let chain: Vec<&(dyn StdError + 'static)> = self.error.chain().collect();
+        let primary = self.error.downcast_ref::<E>();
+        let index = primary.and_then(|e| chain_position(&chain, e as *const E as *const ()));
+        let (mut error, layers) = match (primary, index) {
+            // The primary error is the link itself, so its sources come with it.
+            (Some(e), Some(i)) if chain[i].is::<E>() => {
+                (anyhow::Error::new(e.clone()), &chain[..i])
+            }
+            _ => {
+                let (root, layers) = chain.split_last().expect("chain is never empty");
+                (anyhow::Error::msg(root.to_string()), layers)
+            }
+        };
+        for (i, layer) in layers.iter().enumerate().rev() {
+            error = match primary {
+                Some(e) if index == Some(i) => error.context(e.clone()),
+                _ => error.context(layer.to_string()),
+            };
+        }
         TError {
-            phantom: std::marker::PhantomData,
+            phantom: core::marker::PhantomData,
             error,
+            location: self.location,
+            contexts: self.contexts,
+            extra: self.extra.clone(),
         }
     }
+}
 
-    /// Add context to the error.
-    pub fn with_context<F, R>(self, context: F) -> TError<E>
-    where
-        F: FnOnce() -> R,
-        R: Display + Send + Sync + 'static,
-    {
-        self.context(context())
+/// Two errors are equal if they both contain an error of the primary type E
+/// and those errors are equal.
+///
+/// If neither error contains an error of type E, the comparison falls back
+/// to the `Display` output of the whole chain (as per `{:#}`). This is lossy,
+/// since different errors may have the same message. An error that contains
+/// an error of type E is never equal to one that does not.
+impl<E: PartialEq + Debug + Display + Send + Sync + 'static> PartialEq for TError<E> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.get_ref(), other.get_ref()) {
+            (Some(a), Some(b)) => a == b,
+            (None, None) => format!("{self:#}") == format!("{other:#}"),
+            _ => false,
+        }
     }
+}
 
-    /// Change the generic error type.
-    pub fn change_err<T>(self) -> TError<T> {
-        TError::<T> {
-            phantom: std::marker::PhantomData,
-            error: self.error,
+impl<E: Eq + Debug + Display + Send + Sync + 'static> Eq for TError<E> {}
+
+/// Hash the error of the primary type E if present, otherwise the `Display`
+/// output of the whole chain (as per `{:#}`).
+///
+/// This is consistent with the `PartialEq` impl, and is lossy in the same way.
+impl<E: core::hash::Hash + Debug + Display + Send + Sync + 'static> core::hash::Hash for TError<E> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        match self.get_ref() {
+            Some(e) => {
+                state.write_u8(0);
+                e.hash(state);
+            }
+            None => {
+                state.write_u8(1);
+                format!("{self:#}").hash(state);
+            }
         }
     }
 }
 
-impl<E: Default + Debug + Display + Send + Sync + 'static> TError<E> {}
+/// Options for rendering an error using `TError::format()`.
+///
+/// This allows the rendering to be configured in one place, for example
+/// depending on the environment, without changing the `Display` impl.
+///
+/// ```ignore
+/// let opts = FormatOptions::new().separator(" <- ").include_backtrace(true);
+/// log::error!("{}", err.format(&opts));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Include the whole chain of errors, rather than just the top-level message.
+    /// Defaults to `true`.
+    pub include_chain: bool,
+    /// Include the backtrace on the following lines, if one was captured.
+    /// This is ignored without the `std` feature. Defaults to `false`.
+    pub include_backtrace: bool,
+    /// The separator between the errors in the chain. Defaults to `": "`.
+    pub separator: String,
+}
 
-impl<SRC: StdError + Send + Sync + 'static, DST: StdError + 'static> From<SRC> for TError<DST> {
-    fn from(err: SRC) -> Self {
-        let error = anyhow::Error::new(err);
+impl Default for FormatOptions {
+    fn default() -> Self {
         Self {
-            phantom: std::marker::PhantomData,
-            error,
+            include_chain: true,
+            include_backtrace: false,
+            separator: ": ".to_string(),
         }
     }
 }
 
-pub(crate) mod private {
-    pub trait Sealed {}
-}
+impl FormatOptions {
+    /// Create the default options, which render the same as `{:#}`.
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-/// Extension trait for `Result` to add context to the `Result`.
-pub trait Context<T, E, X: Display>: private::Sealed {
-    /// Wrap the error value with additional context.
-    fn context<C>(self, context: C) -> std::result::Result<T, TError<X>>
-    where
-        C: Display + Send + Sync + 'static;
+    /// Set whether to include the whole chain of errors.
+    pub fn include_chain(mut self, include_chain: bool) -> Self {
+        self.include_chain = include_chain;
+        self
+    }
 
-    /// Wrap the error value with additional context that is evaluated lazily
-    /// only once an error does occur.
-    fn with_context<C, F>(self, f: F) -> std::result::Result<T, TError<X>>
-    where
-        C: Display + Send + Sync + 'static,
-        F: FnOnce() -> C;
+    /// Set whether to include the backtrace, if one was captured.
+    pub fn include_backtrace(mut self, include_backtrace: bool) -> Self {
+        self.include_backtrace = include_backtrace;
+        self
+    }
+
+    /// Set the separator between the errors in the chain.
+    pub fn separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
 }
 
-impl<T, E> private::Sealed for std::result::Result<T, E> {}
+/// Helper for `TError::display_chain()`.
+struct DisplayChain<'a>(&'a anyhow::Error);
 
-impl<T, E: StdError + Send + Sync + 'static, X: StdError> Context<T, E, X>
-    for std::result::Result<T, E>
-{
-    fn context<C>(self, context: C) -> std::result::Result<T, TError<X>>
-    where
-        C: Display + Send + Sync + 'static,
-    {
-        self.map_err(|err| {
-            let error = anyhow::Error::new(err);
-            let error = error.context(context.to_string());
-            TError {
-                phantom: std::marker::PhantomData,
-                error,
+impl Display for DisplayChain<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (i, error) in self.0.chain().enumerate() {
+            if i > 0 {
+                f.write_str(": ")?;
             }
-        })
+            write!(f, "{error}")?;
+        }
+        Ok(())
     }
+}
 
-    fn with_context<C, F>(self, f: F) -> std::result::Result<T, TError<X>>
-    where
-        C: Display + Send + Sync + 'static,
-        F: FnOnce() -> C,
-    {
-        self.context(f())
+/// Allows calling any `anyhow::Error` method directly on a `TError`.
+///
+/// Inherent methods on `TError` always take precedence over methods with the
+/// same name on `anyhow::Error`. For example, `err.context()` returns a
+/// `TError` rather than an `anyhow::Error`, and `err.downcast()` returns a
+/// `TError` on failure.
+impl<E> core::ops::Deref for TError<E> {
+    type Target = anyhow::Error;
+
+    fn deref(&self) -> &Self::Target {
+        &self.error
     }
 }
 
-/// Extension trait to allow capturing errors into a "default" bucket.
-///
-/// This is useful for collecting errors that you want to report on but
-/// don't need to match on. You can use this with a catch-all enum variant
-/// that contains either contains `anyhow::Error` or
-/// `Box<dyn Error + Send + Sync + 'static>`.
+/// Convert a `TError` into the underlying `anyhow::Error`.
 ///
-/// Any errors that don't match the primary error type will be captured
-/// in the default error variant.
-pub trait DefaultError {
-    /// Construct an error variant from the given error.
-    fn from_anyhow(err: anyhow::Error) -> Self;
+/// The whole chain is preserved, so converting it back using
+/// `TError::from_anyhow()` restores the primary error returned by `get_ref()`.
+/// Notes added with `TError::note()` are not part of the chain, so they are lost.
+impl<T> From<TError<T>> for anyhow::Error {
+    fn from(err: TError<T>) -> Self {
+        err.error
+    }
 }
 
-impl<E: DefaultError + Debug + Display + Send + Sync + 'static> TError<E> {
-    /// Get the most recent error of the default type E, or the default error.
-    ///
-    /// If no error was found of type E, then the error is converted into
-    /// type E using the DefaultError trait instead.
-    pub fn get(self) -> E {
-        self.try_get()
-            .unwrap_or_else(|err| E::from_anyhow(err.error))
+impl<T> From<TError<T>> for Box<dyn StdError> {
+    fn from(err: TError<T>) -> Self {
+        err.error.into()
     }
 }
 
-/// Trait to convert something to a `Result<T, TError<E>>`.
-pub trait IntoTError<T, E>: private::Sealed {
-    fn terror(self) -> std::result::Result<T, TError<E>>;
+impl<T> From<TError<T>> for Box<dyn StdError + Send + Sync + 'static> {
+    fn from(err: TError<T>) -> Self {
+        err.error.into()
+    }
 }
 
-impl<T, EIn, EOut> IntoTError<T, EOut> for std::result::Result<T, EIn>
-where
-    EIn: Into<EOut>,
-    EOut: std::error::Error + Send + Sync + 'static,
-{
-    /// Convert `Result<T, EIn>` into `Result<T, TError<EOut>>` where `EIn: Into<EOut>`.
-    fn terror(self) -> std::result::Result<T, TError<EOut>> {
-        self.map_err(|e| TError {
-            phantom: std::marker::PhantomData,
-            error: anyhow::Error::new(e.into()),
-        })
+#[cfg(feature = "std")]
+impl<E> std::process::Termination for TError<E> {
+    fn report(self) -> std::process::ExitCode {
+        eprintln!("Error: {:?}", self);
+        std::process::ExitCode::FAILURE
     }
 }
 
-/// Trait to wrap a `Result<T, TError<A>>` with `Result<T, TError<B>>`.
-pub trait WrapTError<T, E>: private::Sealed {
-    fn change_err(self) -> std::result::Result<T, TError<E>>;
+// NOTE: `TError` cannot implement `core::error::Error` directly, because that
+// would overlap with the blanket `From<SRC: Error>` impl below (the same
+// reason `anyhow::Error` does not implement it). These impls allow `TError`
+// to be used anywhere a `&dyn Error` is expected instead.
+impl<E> AsRef<dyn StdError + Send + Sync> for TError<E> {
+    fn as_ref(&self) -> &(dyn StdError + Send + Sync + 'static) {
+        self.error.as_ref()
+    }
 }
 
-impl<T, EIn, EOut> WrapTError<T, EOut> for std::result::Result<T, TError<EIn>>
-where
-    EIn: std::error::Error + Send + Sync + 'static,
-    EOut: std::error::Error + Send + Sync + 'static,
-{
-    /// Convert `Result<T, EIn>` into `Result<T, TError<EOut>>` where `EIn: Into<EOut>`.
-    fn change_err(self) -> std::result::Result<T, TError<EOut>> {
-        self.map_err(|e| e.change_err())
+impl<E> AsRef<dyn StdError> for TError<E> {
+    fn as_ref(&self) -> &(dyn StdError + 'static) {
+        self.error.as_ref()
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use assert_matches::assert_matches;
-
-    use super::*;
-
-    #[derive(Debug, thiserror::Error)]
-    enum MyError {
-        #[error("something went wrong")]
-        One,
-        #[error("Error two")]
-        Two(Box<dyn StdError + Send + Sync + 'static>),
-        #[error("io error: {0}")]
-        Three(#[from] std::io::Error),
+impl<E> TError<E> {
+    #[track_caller]
+    pub fn from_anyhow(error: anyhow::Error) -> Self {
+        Self {
+            phantom: core::marker::PhantomData,
+            error,
+            location: caller_location(),
+            contexts: 0,
+            extra: Extra::capture(),
+        }
     }
 
-    impl DefaultError for MyError {
-        fn from_anyhow(err: anyhow::Error) -> Self {
-            MyError::Two(err.into())
+    /// Construct a new `TError` from a message.
+    ///
+    /// The message can be anything that implements `Display`, such as
+    /// `&str`, `String` or `Cow<str>`.
+    #[track_caller]
+    pub fn from_msg<M: Display>(msg: M) -> Self {
+        Self {
+            phantom: core::marker::PhantomData,
+            error: anyhow::anyhow!("{}", msg),
+            location: caller_location(),
+            contexts: 0,
+            extra: Extra::capture(),
         }
     }
 
-    #[derive(Debug, PartialEq)]
-    struct OtherError;
-
-    impl Display for OtherError {
-        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            write!(f, "OtherError")
+    /// Construct a new `TError` from a machine-readable code and a message.
+    ///
+    /// The error is a `CodedError`, which displays the message. The code can
+    /// be read back using `code()`, or from the `CodedError` itself using
+    /// `downcast_ref()`.
+    #[track_caller]
+    pub fn coded<M: Display>(code: &'static str, msg: M) -> Self {
+        Self {
+            phantom: core::marker::PhantomData,
+            error: anyhow::Error::new(CodedError::new(code, msg)),
+            location: caller_location(),
+            contexts: 0,
+            extra: Extra::capture(),
         }
     }
 
-    impl StdError for OtherError {}
-
-    fn do_other_task(fail: bool) -> std::result::Result<(), OtherError> {
-        if fail {
-            Err(OtherError)
-        } else {
-            Ok(())
+    /// Construct a new `TError` from a boxed error.
+    ///
+    /// This is useful for errors from APIs that return
+    /// `Box<dyn Error + Send + Sync>`. The `Display` output and the chain of
+    /// sources of the boxed error are preserved. The box itself can be retrieved
+    /// using `downcast_ref::<Box<dyn Error + Send + Sync>>()`, and `find_ref()`
+    /// also finds the boxed error by its concrete type.
+    #[track_caller]
+    pub fn from_boxed(error: Box<dyn StdError + Send + Sync + 'static>) -> Self {
+        Self {
+            phantom: core::marker::PhantomData,
+            error: anyhow::Error::from_boxed(error),
+            location: caller_location(),
+            contexts: 0,
+            extra: Extra::capture(),
         }
     }
 
-    fn fallible_fn(other: bool) -> std::result::Result<(), TError<MyError>> {
-        do_other_task(other)?;
+    /// Convert this error into the underlying `anyhow::Error`.
+    pub fn into_anyhow(self) -> anyhow::Error {
+        self.error
+    }
 
-        Err(MyError::One).context("failed")
+    /// Get a reference to the underlying `anyhow::Error`.
+    pub fn as_anyhow(&self) -> &anyhow::Error {
+        &self.error
     }
 
-    #[test]
-    fn test_err() {
-        let err = fallible_fn(false).unwrap_err();
-        assert_matches!(err.get_ref(), Some(&MyError::One));
-        assert_eq!(format!("{err}"), "failed");
+    /// Convert this error into a `std::io::Error`.
+    ///
+    /// If this error is an `io::Error` without any context, it is returned
+    /// directly. If there is context on top of an `io::Error`, the context is
+    /// kept and the original error kind is used. Otherwise the error kind is
+    /// `ErrorKind::Other`. In all cases the full chain is available via `source()`.
+    #[cfg(feature = "std")]
+    pub fn into_io_error(self) -> std::io::Error {
+        let kind = match self.error.downcast_ref::<std::io::Error>() {
+            Some(e) => e.kind(),
+            None => std::io::ErrorKind::Other,
+        };
+        let error = if self.error.chain().len() == 1 {
+            match self.error.downcast::<std::io::Error>() {
+                Ok(e) => return e,
+                Err(error) => error,
+            }
+        } else {
+            self.error
+        };
+        std::io::Error::new(kind, error)
+    }
 
-        let e2 = err
-            .context("add more context")
-            .context("and even more context");
+    /// The lower-level source of this error, if any.
+    ///
+    /// This is the next link in the underlying `anyhow::Error` chain.
+    pub fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.error.source()
+    }
 
-        assert_matches!(e2.get_ref(), Some(&MyError::One));
+    /// The type name of the primary error type E, for diagnostics.
+    ///
+    /// This is available even if no error of type E is present.
+    pub fn primary_type_name(&self) -> &'static str {
+        core::any::type_name::<E>()
+    }
 
-        let e3 = e2.context(MyError::Two(anyhow::anyhow!("other error").into()));
-        assert_matches!(e3.get_ref(), Some(&MyError::Two(_)));
+    /// Iterate over the chain of errors, starting with this error and
+    /// ending with the root cause.
+    ///
+    /// Any context layers added via `.context()` are included, most recent first.
+    pub fn chain(&self) -> anyhow::Chain<'_> {
+        self.error.chain()
+    }
 
-        let err = fallible_fn(true).unwrap_err();
-        assert_matches!(err.get_ref(), None);
-        assert_eq!(err.downcast_ref(), Some(&OtherError));
-        assert_matches!(err.get(), MyError::Two(_)); // We got some other error.
+    /// Collect the message of each error in the chain, most recent first.
+    ///
+    /// This is an owned snapshot of `chain()`, which is useful for sending
+    /// the chain across a channel or storing it.
+    pub fn chain_strings(&self) -> Vec<String> {
+        self.error.chain().map(|e| e.to_string()).collect()
     }
 
-    #[test]
-    fn test_terror() {
+    /// Iterate over the context layers of this error, most recent first.
+    ///
+    /// Only context added via `TError::context()` or the `Context` trait is
+    /// included. Context that was already present on an `anyhow::Error` before
+    /// it was converted into a `TError` cannot be told apart from the errors
+    /// underneath it, so it is not included. Likewise, context added to an
+    /// `Option` becomes the error itself rather than a context layer.
+    pub fn contexts(&self) -> impl Iterator<Item = String> + '_ {
+        self.error
+            .chain()
+            .take(self.contexts)
+            .map(|e| e.to_string())
+    }
+
+    /// Iterate over the source locations where each context layer was added,
+    /// most recent first.
+    ///
+    /// This lines up with the items returned by `contexts()`. Locations are
+    /// only recorded when the `location` feature is enabled, otherwise this
+    /// iterator is always empty.
+    pub fn context_locations(&self) -> impl Iterator<Item = &'static Location<'static>> + '_ {
+        #[cfg(feature = "location")]
+        let locations: &[&'static Location<'static>] = match &self.extra {
+            Some(extra) => &extra.context_locations,
+            None => &[],
+        };
+        #[cfg(not(feature = "location"))]
+        let locations: &[&'static Location<'static>] = &[];
+        locations.iter().rev().copied()
+    }
+
+    /// Display the whole chain of errors on one line, separated by `": "`.
+    ///
+    /// This is the same as the alternate `{:#}` format, but does not depend
+    /// on the format specifier used by the caller.
+    pub fn display_chain(&self) -> impl Display + '_ {
+        DisplayChain(&self.error)
+    }
+
+    /// Render the error according to the given options.
+    ///
+    /// Any notes are included after the message, as with `Display`.
+    pub fn format(&self, opts: &FormatOptions) -> String {
+        let mut out = if opts.include_chain {
+            self.chain_strings().join(&opts.separator)
+        } else {
+            self.error.to_string()
+        };
+        for note in self.notes() {
+            out.push(' ');
+            out.push_str(note);
+        }
+        #[cfg(feature = "std")]
+        if opts.include_backtrace && self.has_backtrace() {
+            out.push_str(&format!("\n\nStack backtrace:\n{}", self.backtrace()));
+        }
+        out
+    }
+
+    /// The lowest level cause of this error.
+    ///
+    /// This is the last error in the chain, i.e. the original failure
+    /// underneath any context that was added.
+    pub fn root_cause(&self) -> &(dyn StdError + 'static) {
+        self.error.root_cause()
+    }
+
+    /// Get the backtrace for this error.
+    ///
+    /// A backtrace is only captured if the `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE`
+    /// environment variables are set. Use `Backtrace::status()` to check whether
+    /// one was actually captured.
+    #[cfg(feature = "std")]
+    pub fn backtrace(&self) -> &std::backtrace::Backtrace {
+        self.error.backtrace()
+    }
+
+    /// Returns true if a backtrace was captured for this error.
+    ///
+    /// This can be used to avoid logging an empty "disabled backtrace"
+    /// placeholder when backtraces are not enabled.
+    #[cfg(feature = "std")]
+    pub fn has_backtrace(&self) -> bool {
+        self.error.backtrace().status() == std::backtrace::BacktraceStatus::Captured
+    }
+
+    /// The source location where this error was created.
+    ///
+    /// The location is captured by `terror!`, `bail!`, the `?` operator and
+    /// the other `TError` constructors, and is preserved when adding context
+    /// or changing the error type. It is only recorded when the `location`
+    /// feature is enabled, and is always `None` otherwise.
+    pub fn location(&self) -> Option<&'static Location<'static>> {
+        #[cfg(feature = "location")]
+        return Some(self.location.location);
+        #[cfg(not(feature = "location"))]
+        None
+    }
+
+    /// The time when this error was created.
+    ///
+    /// Like `location()`, this is preserved when adding context or changing
+    /// the error type.
+    #[cfg(feature = "capture-meta")]
+    pub fn created_at(&self) -> std::time::SystemTime {
+        self.meta().created_at
+    }
+
+    /// The name of the thread on which this error was created, if it has one.
+    ///
+    /// Like `location()`, this is preserved when adding context or changing
+    /// the error type.
+    #[cfg(feature = "capture-meta")]
+    pub fn thread_name(&self) -> Option<&str> {
+        self.meta().thread.name()
+    }
+
+    /// Returns true if the most recent error of type T is present.
+    pub fn is<T: Debug + Display + Send + Sync + 'static>(&self) -> bool {
+        self.error.is::<T>()
+    }
+
+    /// Get the most recent error of type T.
+    pub fn downcast_ref<T: Debug + Display + Send + Sync + 'static>(&self) -> Option<&T> {
+        self.error.downcast_ref::<T>()
+    }
+
+    /// Get a mutable reference to the most recent error of type T.
+    pub fn downcast_mut<T: Debug + Display + Send + Sync + 'static>(&mut self) -> Option<&mut T> {
+        self.error.downcast_mut::<T>()
+    }
+
+    /// Find the most recent error of type T anywhere in the chain.
+    ///
+    /// This first tries `downcast_ref()`, which finds errors added as context,
+    /// such as with `with_primary()`, and then looks inside an error created
+    /// with `from_boxed()`. Otherwise it walks every link in the chain, so it
+    /// also finds errors that are the source of another error.
+    pub fn find_ref<T: StdError + Send + Sync + 'static>(&self) -> Option<&T> {
+        self.error
+            .downcast_ref::<T>()
+            .or_else(|| {
+                self.error
+                    .downcast_ref::<Box<dyn StdError + Send + Sync>>()
+                    .and_then(|boxed| boxed.downcast_ref::<T>())
+            })
+            .or_else(|| self.error.chain().find_map(|e| e.downcast_ref::<T>()))
+    }
+
+    pub fn downcast<T: Debug + Display + Send + Sync + 'static>(self) -> Result<T, Self> {
+        let location = self.location;
+        let contexts = self.contexts;
+        let extra = self.extra;
+        self.error.downcast::<T>().map_err(|e| TError {
+            phantom: core::marker::PhantomData,
+            error: e,
+            location,
+            contexts,
+            extra,
+        })
+    }
+
+    /// Add context to the error.
+    ///
+    /// Only the `Display` output of the context is kept, so adding context
+    /// never changes the error returned by `get_ref()`, even if the context
+    /// is itself of the primary type E. Use `cause()` to add an error that
+    /// can be matched on.
+    #[track_caller]
+    pub fn context<C>(self, context: C) -> TError<E>
+    where
+        C: Display + Send + Sync + 'static,
+    {
+        self.push_context(DisplayOnly(context))
+    }
+
+    /// Add context to the error.
+    #[track_caller]
+    pub fn with_context<F, R>(self, context: F) -> TError<E>
+    where
+        F: FnOnce() -> R,
+        R: Display + Send + Sync + 'static,
+    {
+        self.context(context())
+    }
+
+    /// Add context to the error, where computing the context may itself fail.
+    ///
+    /// This is useful when the context is looked up, such as a name by ID.
+    /// If the closure returns an error, the fallback context
+    /// `"failed to compute context: {error}"` is added instead. Either way
+    /// the primary error is preserved.
+    #[track_caller]
+    pub fn and_context<F, C, CE>(self, context: F) -> TError<E>
+    where
+        F: FnOnce() -> core::result::Result<C, CE>,
+        C: Display,
+        CE: Display,
+    {
+        match context() {
+            Ok(c) => self.push_context(c.to_string()),
+            Err(e) => self.push_context(alloc::format!("failed to compute context: {e}")),
+        }
+    }
+
+    /// Add a new error on top of this one, with this error as its source.
+    ///
+    /// Unlike `context()`, the new error can be downcast. If it is of the
+    /// primary type E, it becomes the most recent error returned by `get_ref()`.
+    pub fn cause<C>(self, error: C) -> TError<E>
+    where
+        C: Debug + Display + Send + Sync + 'static,
+    {
+        TError {
+            phantom: core::marker::PhantomData,
+            error: self.error.context(error),
+            location: self.location,
+            contexts: 0,
+            extra: Extra::without_contexts(self.extra),
+        }
+    }
+
+    /// Add a trailing note to the error, such as "(see logs for details)".
+    ///
+    /// Unlike context, notes are not part of the chain. They are displayed
+    /// after the main message, separated by a space, and on separate lines
+    /// after the chain in the `Debug` output.
+    pub fn note<C>(mut self, note: C) -> TError<E>
+    where
+        C: Display,
+    {
+        self.extra_mut().notes.push(note.to_string());
+        self
+    }
+
+    /// Iterate over the notes added to this error, in the order they were added.
+    pub fn notes(&self) -> impl Iterator<Item = &str> + '_ {
+        self.extra
+            .iter()
+            .flat_map(|extra| &extra.notes)
+            .map(String::as_str)
+    }
+
+    /// Attach a typed value to the error, such as a request ID.
+    ///
+    /// The value is added to the chain in the same way as context, so it is
+    /// included when displaying the error. It can be retrieved again using
+    /// `downcast_ref()`, even after more context has been added on top.
+    #[track_caller]
+    pub fn attach<T>(self, value: T) -> TError<E>
+    where
+        T: Debug + Display + Send + Sync + 'static,
+    {
+        self.push_context(value)
+    }
+
+    /// Add a context layer to the underlying error and record its location.
+    #[track_caller]
+    #[cfg_attr(not(feature = "location"), allow(unused_mut))]
+    fn push_context<C>(mut self, context: C) -> TError<E>
+    where
+        C: Display + Debug + Send + Sync + 'static,
+    {
+        #[cfg(feature = "location")]
+        self.extra_mut().context_locations.push(Location::caller());
+        TError {
+            phantom: core::marker::PhantomData,
+            error: self.error.context(context),
+            location: self.location,
+            contexts: self.contexts + 1,
+            extra: self.extra,
+        }
+    }
+
+    /// The rarely used details of this error, allocating them if needed.
+    fn extra_mut(&mut self) -> &mut Extra {
+        self.extra.get_or_insert_with(|| Box::new(Extra::new()))
+    }
+
+    /// The metadata captured when this error was created.
+    #[cfg(feature = "capture-meta")]
+    fn meta(&self) -> &Meta {
+        &self
+            .extra
+            .as_ref()
+            .expect("metadata is captured when the error is created")
+            .meta
+    }
+
+    /// Combine several errors into a single error.
+    ///
+    /// The combined error displays each of the individual errors along with
+    /// their context and notes. `get_ref()` will return the first error of the primary
+    /// type E, if any. The individual errors can be retrieved again by
+    /// downcasting to `MultiError`.
+    ///
+    /// If only one error is given, it is returned unchanged. If no errors
+    /// are given, there is nothing to combine, so `None` is returned.
+    #[track_caller]
+    pub fn combine(mut errors: Vec<TError<E>>) -> Option<TError<E>> {
+        match errors.len() {
+            0 => None,
+            1 => Some(errors.remove(0)),
+            _ => {
+                let (errors, notes) = errors
+                    .into_iter()
+                    .map(|e| {
+                        (
+                            e.error,
+                            e.extra.map(|extra| extra.notes).unwrap_or_default(),
+                        )
+                    })
+                    .unzip();
+                Some(TError::from_anyhow(anyhow::Error::new(MultiError::new(
+                    errors, notes,
+                ))))
+            }
+        }
+    }
+
+    /// Change the generic error type.
+    pub fn change_err<T>(self) -> TError<T> {
+        TError::<T> {
+            phantom: core::marker::PhantomData,
+            error: self.error,
+            location: self.location,
+            contexts: self.contexts,
+            extra: self.extra,
+        }
+    }
+}
+
+impl<E: Debug + Display + Send + Sync + 'static> TError<E> {
+    /// Get the most recent error of the default type E.
+    ///
+    /// As with `get_ref()`, if this error was created using `TError::combine()`,
+    /// the first combined error of type E is returned.
+    pub fn try_get(self) -> Result<E, TError<E>> {
+        if !self.error.is::<E>() && self.get_ref().is_some() {
+            let multi = self
+                .error
+                .downcast::<MultiError>()
+                .expect("the error of type E is in a MultiError");
+            let mut errors = multi.into_errors();
+            let index = errors
+                .iter()
+                .position(|e| e.is::<E>())
+                .expect("the MultiError contains an error of type E");
+            return Ok(errors
+                .swap_remove(index)
+                .downcast()
+                .expect("the error is of type E"));
+        }
+        let location = self.location;
+        let contexts = self.contexts;
+        let extra = self.extra;
+        self.error.downcast().map_err(|e| TError {
+            phantom: core::marker::PhantomData,
+            error: e,
+            location,
+            contexts,
+            extra,
+        })
+    }
+
+    /// Get the most recent error of the default type E, or `default` if not present.
+    ///
+    /// This is like `get()`, but does not require E to implement `DefaultError`.
+    pub fn get_or(self, default: E) -> E {
+        self.try_get().unwrap_or(default)
+    }
+
+    /// Get the most recent error of the default type E, or compute it from the
+    /// underlying error if not present.
+    ///
+    /// This is like `get()`, but does not require E to implement `DefaultError`.
+    pub fn get_or_else<F>(self, f: F) -> E
+    where
+        F: FnOnce(anyhow::Error) -> E,
+    {
+        self.try_get().unwrap_or_else(|err| f(err.error))
+    }
+
+    /// Get the most recent error of the default type E.
+    ///
+    /// If this error was created using `TError::combine()`, the first
+    /// combined error of type E is returned.
+    pub fn get_ref(&self) -> Option<&E> {
+        self.error.downcast_ref::<E>().or_else(|| {
+            self.error
+                .downcast_ref::<MultiError>()?
+                .errors()
+                .iter()
+                .find_map(|e| e.downcast_ref::<E>())
+        })
+    }
+
+    /// Construct a new `TError` from an `anyhow::Error`, only if it contains
+    /// an error of the primary type E.
+    ///
+    /// This is the strict counterpart to `from_anyhow()`, which accepts any
+    /// error. If no error of type E is present, the original error is returned
+    /// unchanged.
+    ///
+    /// NOTE: This is not a `TryFrom` impl, because that would overlap with the
+    /// blanket `TryFrom` impl for types that implement `Into`, in case
+    /// `anyhow::Error` ever implements `core::error::Error`.
+    #[track_caller]
+    pub fn try_from_anyhow(error: anyhow::Error) -> core::result::Result<Self, anyhow::Error> {
+        let err = TError::from_anyhow(error);
+        if err.has_primary() {
+            Ok(err)
+        } else {
+            Err(err.error)
+        }
+    }
+
+    /// Returns true if an error of the default type E is present.
+    ///
+    /// This is the same as `get_ref().is_some()`.
+    pub fn has_primary(&self) -> bool {
+        self.get_ref().is_some()
+    }
+
+    /// Assert that an error of the default type E is present, in debug builds only.
+    ///
+    /// This helps catch errors that were not converted into the primary error
+    /// type during development, for example where `.terror()` was forgotten.
+    /// In release builds the error is returned unchanged without any check.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if no error of type E is present.
+    #[track_caller]
+    pub fn debug_assert_primary(self) -> Self {
+        debug_assert!(
+            self.has_primary(),
+            "expected a primary error of type {}, got: {:#}",
+            self.primary_type_name(),
+            self
+        );
+        self
+    }
+
+    /// Returns true if the most recent error of the default type E is the
+    /// same enum variant as `other`, ignoring any data in the variant.
+    ///
+    /// This compares the variants using `core::mem::discriminant()`, so E
+    /// does not need to implement `PartialEq`. Returns false if no error of
+    /// type E is present.
+    pub fn same_kind(&self, other: &E) -> bool {
+        self.get_ref()
+            .is_some_and(|e| core::mem::discriminant(e) == core::mem::discriminant(other))
+    }
+
+    /// Get the most recent error of the default type E, without consuming the error.
+    ///
+    /// This is the borrowing equivalent of `try_get()`. If no error of type E
+    /// is present, the underlying `anyhow::Error` is returned instead.
+    pub fn try_get_ref(&self) -> core::result::Result<&E, &anyhow::Error> {
+        self.get_ref().ok_or(&self.error)
+    }
+
+    /// Split this error into the primary error of type E, if present, and the
+    /// rest of the chain.
+    ///
+    /// If the error of type E is present, the returned `anyhow::Error` contains
+    /// the other errors in the chain as plain messages, so they can no longer
+    /// be downcast. If the primary error was the only error in the chain, it
+    /// contains just the message of the primary error.
+    ///
+    /// As with `get_ref()`, if this error was created using `TError::combine()`,
+    /// the first combined error of type E is split out of its entry, and the
+    /// rest is returned as a `MultiError` with the same context on top of it.
+    ///
+    /// If the error of type E is not present, the whole chain is returned unchanged.
+    pub fn into_parts(self) -> (Option<E>, anyhow::Error) {
+        if self.error.is::<E>() || self.get_ref().is_none() {
+            return match split_primary::<E>(self.error) {
+                Ok((e, rest)) => (Some(e), rest),
+                Err(error) => (None, error),
+            };
+        }
+        let layers: Vec<String> = {
+            let chain: Vec<&(dyn StdError + 'static)> = self.error.chain().collect();
+            let multi = self
+                .error
+                .downcast_ref::<MultiError>()
+                .expect("the error of type E is in a MultiError");
+            let index = chain_position(&chain, multi as *const MultiError as *const ())
+                .expect("the MultiError is in the chain");
+            chain[..index].iter().map(|e| e.to_string()).collect()
+        };
+        let multi = self
+            .error
+            .downcast::<MultiError>()
+            .expect("the error of type E is in a MultiError");
+        let (mut errors, notes) = multi.into_parts();
+        let index = errors
+            .iter()
+            .position(|e| e.is::<E>())
+            .expect("the MultiError contains an error of type E");
+        let (e, rest) = split_primary::<E>(errors.remove(index)).expect("the error is of type E");
+        errors.insert(index, rest);
+        let rest = layers.into_iter().rev().fold(
+            anyhow::Error::new(MultiError::new(errors, notes)),
+            |error, msg| error.context(msg),
+        );
+        (Some(e), rest)
+    }
+
+    /// Get a mutable reference to the most recent error of the default type E.
+    ///
+    /// As with `get_ref()`, if this error was created using `TError::combine()`,
+    /// the first combined error of type E is returned.
+    pub fn get_mut(&mut self) -> Option<&mut E> {
+        if self.error.is::<E>() {
+            return self.error.downcast_mut::<E>();
+        }
+        self.error
+            .downcast_mut::<MultiError>()?
+            .errors_mut()
+            .iter_mut()
+            .find_map(|e| e.downcast_mut::<E>())
+    }
+
+    /// Install `error` as the new primary error, keeping the existing chain as its source.
+    ///
+    /// This is useful for classifying a lower-level error into a specific
+    /// variant of the primary error type. `get_ref()` will return the new error,
+    /// and `chain()` continues with the previous errors and their context.
+    pub fn with_primary(self, error: E) -> TError<E> {
+        TError {
+            phantom: core::marker::PhantomData,
+            error: self.error.context(error),
+            location: self.location,
+            contexts: 0,
+            extra: Extra::without_contexts(self.extra),
+        }
+    }
+
+    /// Replace the primary error with `new` if it matches `pred`.
+    ///
+    /// If the error of type E is present and `pred` returns true for it,
+    /// `new` becomes the primary error, with the existing chain as its source,
+    /// as with `with_primary()`. Otherwise the error is returned unchanged.
+    pub fn escalate_if<F>(self, pred: F, new: E) -> TError<E>
+    where
+        F: FnOnce(&E) -> bool,
+    {
+        if self.get_ref().is_some_and(pred) {
+            self.with_primary(new)
+        } else {
+            self
+        }
+    }
+
+    /// Merge another error into this one, such as a cleanup failure that
+    /// occurred while handling this error.
+    ///
+    /// The chain of `self` is kept intact, so its primary error can still be
+    /// matched and downcast. The full chain of `other` is added as a note,
+    /// prefixed with "also: ", so both errors are visible when displayed.
+    /// Note that `other` can no longer be downcast after merging.
+    ///
+    /// ```ignore
+    /// if let Err(err) = do_work() {
+    ///     return Err(match cleanup() {
+    ///         Ok(()) => err,
+    ///         Err(cleanup_err) => err.merge(cleanup_err),
+    ///     });
+    /// }
+    /// ```
+    pub fn merge(self, other: TError<E>) -> TError<E> {
+        self.note(format!("also: {other:#}"))
+    }
+
+    /// Add context to the error, based on the most recent error of the default type E.
+    ///
+    /// This lets the context mention the specific variant, e.g.
+    /// "while handling a NotFound for id X". The closure receives `None` if no
+    /// error of type E is present.
+    #[track_caller]
+    pub fn with_context_for<F, R>(self, context: F) -> TError<E>
+    where
+        F: FnOnce(Option<&E>) -> R,
+        R: Display + Send + Sync + 'static,
+    {
+        let context = context(self.get_ref());
+        self.context(context)
+    }
+
+    /// Convert the primary error of type E into a new primary error of type F.
+    ///
+    /// If the error of type E is present, it is passed to `f` and the result
+    /// becomes the new error. Note that any context that was added on top
+    /// of the primary error is discarded in this case.
+    ///
+    /// If the error of type E is not present, `f` is not called and the
+    /// original error is returned unchanged, with F as the new generic type.
+    pub fn map_primary<F, Func>(self, f: Func) -> TError<F>
+    where
+        F: StdError + Send + Sync + 'static,
+        Func: FnOnce(E) -> F,
+    {
+        let location = self.location;
+        let extra = self.extra.as_deref().and_then(Extra::meta_only);
+        match self.try_get() {
+            Ok(e) => TError {
+                phantom: core::marker::PhantomData,
+                error: anyhow::Error::new(f(e)),
+                location,
+                contexts: 0,
+                extra,
+            },
+            Err(err) => err.change_err(),
+        }
+    }
+
+    /// Change the generic error type, converting the primary error using `Into`.
+    ///
+    /// This is the value-aware counterpart to `change_err()`. If the error of
+    /// type E is present, it is converted into F and becomes the new error,
+    /// as with `map_primary()`. Otherwise the underlying error is kept
+    /// unchanged, with F as the new generic type.
+    pub fn convert_err<F>(self) -> TError<F>
+    where
+        E: Into<F>,
+        F: StdError + Send + Sync + 'static,
+    {
+        self.map_primary(Into::into)
+    }
+}
+
+impl<E: Default + Debug + Display + Send + Sync + 'static> TError<E> {}
+
+impl<E: Clone + Debug + Display + Send + Sync + 'static> TError<E> {
+    /// Get a clone of the most recent error of the default type E, without
+    /// consuming the error.
+    ///
+    /// This is the borrowing equivalent of `try_get().ok()`.
+    pub fn try_get_cloned(&self) -> Option<E> {
+        self.get_ref().cloned()
+    }
+}
+
+impl<E: StdError + Send + Sync + 'static> TError<E> {
+    /// Construct a new `TError` with the given primary error.
+    ///
+    /// The error is stored as the primary error type, so `get_ref()`
+    /// will always return `Some` on the result.
+    #[track_caller]
+    pub fn new(error: E) -> Self {
+        Self {
+            phantom: core::marker::PhantomData,
+            error: anyhow::Error::new(error),
+            location: caller_location(),
+            contexts: 0,
+            extra: Extra::capture(),
+        }
+    }
+
+    /// Construct a new `TError` with the given primary error, caused by `source`.
+    ///
+    /// `get_ref()` will return the primary error, and `chain()` continues
+    /// with the source error and its own sources.
+    #[track_caller]
+    pub fn from_parts<S>(primary: E, source: S) -> Self
+    where
+        S: StdError + Send + Sync + 'static,
+    {
+        Self {
+            phantom: core::marker::PhantomData,
+            error: anyhow::Error::new(source).context(primary),
+            location: caller_location(),
+            contexts: 0,
+            extra: Extra::capture(),
+        }
+    }
+
+    /// Strip all context and return a new `TError` containing only the root cause.
+    ///
+    /// If the root cause is the most recent error of the primary type E, it is
+    /// kept as-is, so `get_ref()` still returns it. Otherwise the root cause
+    /// cannot be moved out of the chain, so the new error only keeps its
+    /// `Display` output.
+    ///
+    /// The location where the original error was created is preserved.
+    pub fn into_root(self) -> TError<E> {
+        let location = self.location;
+        let extra = Extra::without_contexts(self.extra);
+        let root = self.error.root_cause() as *const dyn StdError as *const ();
+        let root_is_primary = self
+            .error
+            .downcast_ref::<E>()
+            .is_some_and(|e| core::ptr::eq(e as *const E as *const (), root));
+        let error = if root_is_primary {
+            match self.error.downcast::<E>() {
+                Ok(e) => anyhow::Error::new(e),
+                Err(error) => anyhow::Error::msg(error.root_cause().to_string()),
+            }
+        } else {
+            anyhow::Error::msg(self.error.root_cause().to_string())
+        };
+        TError {
+            phantom: core::marker::PhantomData,
+            error,
+            location,
+            contexts: 0,
+            extra,
+        }
+    }
+
+    /// Try to convert the primary error of type E into a new primary error of type F.
+    ///
+    /// If the error of type E is present, it is passed to `f` by reference.
+    /// If `f` returns `Some`, the result becomes the new error, as with
+    /// `map_primary()`. Otherwise, or if the error of type E is not present,
+    /// the original error is returned untouched in `Err`.
+    pub fn try_map_primary<F, Func>(self, f: Func) -> core::result::Result<TError<F>, Self>
+    where
+        F: StdError + Send + Sync + 'static,
+        Func: FnOnce(&E) -> Option<F>,
+    {
+        match self.get_ref().and_then(f) {
+            Some(e) => Ok(TError {
+                phantom: core::marker::PhantomData,
+                error: anyhow::Error::new(e),
+                location: self.location,
+                contexts: 0,
+                extra: self.extra.as_deref().and_then(Extra::meta_only),
+            }),
+            None => Err(self),
+        }
+    }
+}
+
+impl<SRC: StdError + Send + Sync + 'static, DST> From<SRC> for TError<DST> {
+    #[track_caller]
+    fn from(err: SRC) -> Self {
+        let error = anyhow::Error::new(err);
+        Self {
+            phantom: core::marker::PhantomData,
+            error,
+            location: caller_location(),
+            contexts: 0,
+            extra: Extra::capture(),
+        }
+    }
+}
+
+pub(crate) mod private {
+    pub trait Sealed {}
+}
+
+/// Extension trait for `Result` and `Option` to add context to the `Result`.
+pub trait Context<T, E, X>: private::Sealed {
+    /// Wrap the error value with additional context.
+    fn context<C>(self, context: C) -> core::result::Result<T, TError<X>>
+    where
+        C: Display + Send + Sync + 'static;
+
+    /// Wrap the error value with additional context that is evaluated lazily
+    /// only once an error does occur.
+    fn with_context<C, F>(self, f: F) -> core::result::Result<T, TError<X>>
+    where
+        C: Display + Send + Sync + 'static,
+        F: FnOnce() -> C;
+
+    /// Wrap the error value with additional context, formatted using `Debug`.
+    ///
+    /// This is useful for attaching values that do not implement `Display`.
+    fn context_debug<C>(self, context: C) -> core::result::Result<T, TError<X>>
+    where
+        C: Debug + Send + Sync + 'static;
+}
+
+impl<T, E> private::Sealed for core::result::Result<T, E> {}
+
+impl<T, E: StdError + Send + Sync + 'static, X> Context<T, E, X> for core::result::Result<T, E> {
+    #[track_caller]
+    fn context<C>(self, context: C) -> core::result::Result<T, TError<X>>
+    where
+        C: Display + Send + Sync + 'static,
+    {
+        self.with_context(|| context)
+    }
+
+    #[track_caller]
+    fn with_context<C, F>(self, f: F) -> core::result::Result<T, TError<X>>
+    where
+        C: Display + Send + Sync + 'static,
+        F: FnOnce() -> C,
+    {
+        let location = caller_location();
+        self.map_err(|err| {
+            let error = anyhow::Error::new(err);
+            let error = error.context(DisplayOnly(f()));
+            TError {
+                phantom: core::marker::PhantomData,
+                error,
+                location,
+                contexts: 1,
+                extra: Extra::for_context(location),
+            }
+        })
+    }
+
+    #[track_caller]
+    fn context_debug<C>(self, context: C) -> core::result::Result<T, TError<X>>
+    where
+        C: Debug + Send + Sync + 'static,
+    {
+        self.with_context(|| format!("{context:?}"))
+    }
+}
+
+/// Extension trait for `Result` to add context when the error only implements `Display`.
+///
+/// This is useful for third-party error types that do not implement
+/// `core::error::Error`. The original error is converted into a message, so it
+/// cannot be downcast again, but its `Display` output is preserved in the chain.
+pub trait DisplayContext<T, X>: private::Sealed {
+    /// Wrap the error value with additional context.
+    fn display_context<C>(self, context: C) -> core::result::Result<T, TError<X>>
+    where
+        C: Display + Send + Sync + 'static;
+
+    /// Wrap the error value with additional context that is evaluated lazily
+    /// only once an error does occur.
+    fn with_display_context<C, F>(self, f: F) -> core::result::Result<T, TError<X>>
+    where
+        C: Display + Send + Sync + 'static,
+        F: FnOnce() -> C;
+}
+
+impl<T, E: Display + Send + Sync + 'static, X> DisplayContext<T, X> for core::result::Result<T, E> {
+    #[track_caller]
+    fn display_context<C>(self, context: C) -> core::result::Result<T, TError<X>>
+    where
+        C: Display + Send + Sync + 'static,
+    {
+        self.with_display_context(|| context)
+    }
+
+    #[track_caller]
+    fn with_display_context<C, F>(self, f: F) -> core::result::Result<T, TError<X>>
+    where
+        C: Display + Send + Sync + 'static,
+        F: FnOnce() -> C,
+    {
+        let location = caller_location();
+        self.map_err(|err| {
+            let error = anyhow::anyhow!("{}", err);
+            let error = error.context(DisplayOnly(f()));
+            TError {
+                phantom: core::marker::PhantomData,
+                error,
+                location,
+                contexts: 1,
+                extra: Extra::for_context(location),
+            }
+        })
+    }
+}
+
+impl<T> private::Sealed for Option<T> {}
+
+impl<T, X> Context<T, core::convert::Infallible, X> for Option<T> {
+    #[track_caller]
+    fn context<C>(self, context: C) -> core::result::Result<T, TError<X>>
+    where
+        C: Display + Send + Sync + 'static,
+    {
+        let location = caller_location();
+        self.ok_or_else(|| TError {
+            phantom: core::marker::PhantomData,
+            error: anyhow::Error::msg(DisplayOnly(context)),
+            location,
+            contexts: 0,
+            extra: Extra::capture(),
+        })
+    }
+
+    #[track_caller]
+    fn with_context<C, F>(self, f: F) -> core::result::Result<T, TError<X>>
+    where
+        C: Display + Send + Sync + 'static,
+        F: FnOnce() -> C,
+    {
+        let location = caller_location();
+        self.ok_or_else(|| TError {
+            phantom: core::marker::PhantomData,
+            error: anyhow::Error::msg(DisplayOnly(f())),
+            location,
+            contexts: 0,
+            extra: Extra::capture(),
+        })
+    }
+
+    #[track_caller]
+    fn context_debug<C>(self, context: C) -> core::result::Result<T, TError<X>>
+    where
+        C: Debug + Send + Sync + 'static,
+    {
+        self.with_context(|| format!("{context:?}"))
+    }
+}
+
+/// Extension trait to allow capturing errors into a "default" bucket.
+///
+/// This is useful for collecting errors that you want to report on but
+/// don't need to match on. You can use this with a catch-all enum variant
+/// that contains either contains `anyhow::Error` or
+/// `Box<dyn Error + Send + Sync + 'static>`.
+///
+/// Any errors that don't match the primary error type will be captured
+/// in the default error variant.
+///
+/// For a type that already implements `From<anyhow::Error>`, such as an
+/// enum with a `#[from] anyhow::Error` variant, use `impl_default_error!`
+/// to implement this trait using that conversion.
+pub trait DefaultError {
+    /// Construct an error variant from the given error.
+    fn from_anyhow(err: anyhow::Error) -> Self;
+}
+
+impl<E: DefaultError + Debug + Display + Send + Sync + 'static> TError<E> {
+    /// Get the most recent error of the default type E, or the default error.
+    ///
+    /// If no error was found of type E, then the error is converted into
+    /// type E using the DefaultError trait instead.
+    pub fn get(self) -> E {
+        self.try_get()
+            .unwrap_or_else(|err| E::from_anyhow(err.error))
+    }
+
+    /// Convert this error into the primary error type E.
+    ///
+    /// This is an alias for `get()`. If no error of type E was found, the
+    /// error is converted into type E using the DefaultError trait instead.
+    pub fn into_primary(self) -> E {
+        self.get()
+    }
+
+    /// Make sure the error of the default type E is present.
+    ///
+    /// If no error was found of type E, the error is converted into type E
+    /// using the DefaultError trait, and that becomes the new primary error.
+    /// Otherwise the error is returned unchanged, including any context.
+    /// Either way, `get_ref()` will always return `Some` on the result.
+    pub fn normalize(self) -> TError<E>
+    where
+        E: StdError,
+    {
+        if self.get_ref().is_some() {
+            return self;
+        }
+        TError {
+            phantom: core::marker::PhantomData,
+            error: anyhow::Error::new(E::from_anyhow(self.error)),
+            location: self.location,
+            contexts: 0,
+            extra: Extra::without_contexts(self.extra),
+        }
+    }
+}
+
+/// Trait for primary error types that map to an HTTP status code.
+///
+/// This is useful in web handlers, where the status code of the response
+/// depends on the variant of the primary error.
+pub trait HttpStatus {
+    /// The HTTP status code for this error, e.g. `404`.
+    fn status_code(&self) -> u16;
+}
+
+impl<E: HttpStatus + Debug + Display + Send + Sync + 'static> TError<E> {
+    /// Get the HTTP status code of the most recent error of the default type E.
+    ///
+    /// Returns `None` if no error of type E was found.
+    pub fn status_code(&self) -> Option<u16> {
+        self.get_ref().map(HttpStatus::status_code)
+    }
+}
+
+/// Trait for primary error types that have a stable, machine-readable code.
+///
+/// This is useful for reporting errors to clients, where the code should not
+/// change even if the error message does.
+pub trait ErrorCode {
+    /// The code for this error, e.g. `"not_found"`.
+    fn code(&self) -> &'static str;
+}
+
+impl<E: ErrorCode + Debug + Display + Send + Sync + 'static> TError<E> {
+    /// Get the code of the most recent error of the default type E.
+    ///
+    /// If no error of type E was found, the code of a `CodedError` created
+    /// with `TError::coded()` is returned instead, if present.
+    pub fn code(&self) -> Option<&'static str> {
+        self.get_ref()
+            .map(ErrorCode::code)
+            .or_else(|| self.error.downcast_ref::<CodedError>().map(ErrorCode::code))
+    }
+}
+
+/// An error with a machine-readable code and a message.
+///
+/// This is useful for errors defined outside of the primary error type, such
+/// as error codes returned by another service. Create one using `TError::coded()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodedError {
+    code: &'static str,
+    message: String,
+}
+
+impl CodedError {
+    /// Create a new error with the given code and message.
+    pub fn new(code: &'static str, message: impl Display) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+        }
+    }
+
+    /// The message of this error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl ErrorCode for CodedError {
+    fn code(&self) -> &'static str {
+        self.code
+    }
+}
+
+impl Display for CodedError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&self.message, f)
+    }
+}
+
+impl StdError for CodedError {}
+
+/// Trait for primary error types whose messages may contain sensitive data.
+///
+/// This is useful for logging errors in production without leaking secrets
+/// such as connection strings.
+pub trait Redact {
+    /// The message for this error with any sensitive data removed.
+    fn redacted(&self) -> String;
+}
+
+impl<E: Redact + Debug + Display + Send + Sync + 'static> TError<E> {
+    /// Display the error with the primary error in its redacted form.
+    ///
+    /// This returns any context added via `TError` followed by the redacted
+    /// primary error, separated by `": "`, and then any notes. If the error of
+    /// type E is not present, the error below the context is shown as is.
+    ///
+    /// Only the output of this method is redacted. `chain()` and the
+    /// other formatting methods still expose the raw messages.
+    pub fn display_redacted(&self) -> String {
+        let primary = match self.get_ref() {
+            Some(e) => e.redacted(),
+            None => self
+                .error
+                .chain()
+                .nth(self.contexts)
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+        };
+        let mut output = self
+            .contexts()
+            .chain(core::iter::once(primary))
+            .collect::<Vec<_>>()
+            .join(": ");
+        for note in self.notes() {
+            output.push(' ');
+            output.push_str(note);
+        }
+        output
+    }
+}
+
+/// Trait for primary error types that can distinguish transient failures
+/// from permanent ones.
+///
+/// This is used by `retry()` to decide whether an operation should be retried.
+pub trait Retryable {
+    /// Returns true if the operation that produced this error may succeed
+    /// if it is attempted again.
+    fn is_transient(&self) -> bool;
+}
+
+impl<E: Retryable + Debug + Display + Send + Sync + 'static> TError<E> {
+    /// Returns true if the most recent error of the default type E is transient.
+    ///
+    /// Returns false if no error of type E was found.
+    pub fn is_transient(&self) -> bool {
+        self.get_ref().is_some_and(Retryable::is_transient)
+    }
+}
+
+/// Trait to convert something to a `Result<T, TError<E>>`.
+pub trait IntoTError<T, E>: private::Sealed {
+    fn terror(self) -> core::result::Result<T, TError<E>>;
+}
+
+impl<T, EIn, EOut> IntoTError<T, EOut> for core::result::Result<T, EIn>
+where
+    EIn: Into<EOut>,
+    EOut: core::error::Error + Send + Sync + 'static,
+{
+    /// Convert `Result<T, EIn>` into `Result<T, TError<EOut>>` where `EIn: Into<EOut>`.
+    #[track_caller]
+    fn terror(self) -> core::result::Result<T, TError<EOut>> {
+        let location = caller_location();
+        self.map_err(|e| TError {
+            phantom: core::marker::PhantomData,
+            error: anyhow::Error::new(e.into()),
+            location,
+            contexts: 0,
+            extra: Extra::capture(),
+        })
+    }
+}
+
+/// Trait to convert a `Result` with a message as the error, such as
+/// `Result<T, String>` or `Result<T, &str>`, into a `Result<T, TError<E>>`.
+pub trait MsgTError<T>: private::Sealed {
+    /// Wrap the error message in a `TError` with the chosen primary error type.
+    ///
+    /// The resulting error displays the original message. It does not contain
+    /// an error of type E, so `get_ref()` will return `None`.
+    fn terror_msg<E>(self) -> core::result::Result<T, TError<E>>;
+}
+
+impl<T, M: Display> MsgTError<T> for core::result::Result<T, M> {
+    #[track_caller]
+    fn terror_msg<E>(self) -> core::result::Result<T, TError<E>> {
+        let location = caller_location();
+        self.map_err(|msg| TError {
+            phantom: core::marker::PhantomData,
+            error: anyhow::anyhow!("{}", msg),
+            location,
+            contexts: 0,
+            extra: Extra::capture(),
+        })
+    }
+}
+
+/// Trait to convert something to a `Result<T, TError<E>>` using a closure.
+///
+/// This is useful when there is no `Into` conversion from the source error
+/// into the primary error type, so `IntoTError::terror()` cannot be used.
+pub trait MapTError<T, EIn>: private::Sealed {
+    /// Convert `Result<T, EIn>` into `Result<T, TError<EOut>>`, using `f` to
+    /// map the error into the primary error type.
+    fn terror_with<EOut, F>(self, f: F) -> core::result::Result<T, TError<EOut>>
+    where
+        EOut: StdError + Send + Sync + 'static,
+        F: FnOnce(EIn) -> EOut;
+}
+
+impl<T, EIn> MapTError<T, EIn> for core::result::Result<T, EIn> {
+    #[track_caller]
+    fn terror_with<EOut, F>(self, f: F) -> core::result::Result<T, TError<EOut>>
+    where
+        EOut: StdError + Send + Sync + 'static,
+        F: FnOnce(EIn) -> EOut,
+    {
+        let location = caller_location();
+        self.map_err(|e| TError {
+            phantom: core::marker::PhantomData,
+            error: anyhow::Error::new(f(e)),
+            location,
+            contexts: 0,
+            extra: Extra::capture(),
+        })
+    }
+}
+
+/// Trait to wrap a `Result<T, TError<A>>` with `Result<T, TError<B>>`.
+///
+/// This is the `Result` equivalent of `TError::change_err()`, and is useful
+/// for passing errors up to a caller that uses a different primary error type.
+pub trait WrapTError<T, E>: private::Sealed {
+    /// Change the generic error type of the `TError` in this `Result`.
+    ///
+    /// The underlying error and its context are left unchanged.
+    fn change_err(self) -> core::result::Result<T, TError<E>>;
+}
+
+impl<T, EIn, EOut> WrapTError<T, EOut> for core::result::Result<T, TError<EIn>> {
+    fn change_err(self) -> core::result::Result<T, TError<EOut>> {
+        self.map_err(|e| e.change_err())
+    }
+}
+
+/// Trait to chain a fallible operation that returns the primary error type
+/// onto a `Result<T, TError<E>>`.
+pub trait AndThenTError<T, E>: private::Sealed {
+    /// Call `f` with the `Ok` value, converting any error it returns into a `TError<E>`.
+    ///
+    /// This is equivalent to `and_then()`, except that the closure can
+    /// return the primary error type E directly.
+    fn and_then_terror<U, F>(self, f: F) -> core::result::Result<U, TError<E>>
+    where
+        F: FnOnce(T) -> core::result::Result<U, E>;
+}
+
+impl<T, E: StdError + Send + Sync + 'static> AndThenTError<T, E>
+    for core::result::Result<T, TError<E>>
+{
+    #[track_caller]
+    fn and_then_terror<U, F>(self, f: F) -> core::result::Result<U, TError<E>>
+    where
+        F: FnOnce(T) -> core::result::Result<U, E>,
+    {
+        let location = caller_location();
+        self.and_then(|t| {
+            f(t).map_err(|e| TError {
+                phantom: core::marker::PhantomData,
+                error: anyhow::Error::new(e),
+                location,
+                contexts: 0,
+                extra: Extra::capture(),
+            })
+        })
+    }
+}
+
+/// Trait to discard the primary error type of a `Result<T, TError<E>>`.
+pub trait EraseTError<T>: private::Sealed {
+    /// Change the generic error type of the `TError` in this `Result` to `()`.
+    ///
+    /// This is shorthand for `.change_err::<()>()`, for when the caller
+    /// no longer needs to match on the primary error type.
+    fn erase_err(self) -> core::result::Result<T, TError<()>>;
+}
+
+impl<T, E> EraseTError<T> for core::result::Result<T, TError<E>> {
+    fn erase_err(self) -> core::result::Result<T, TError<()>> {
+        self.map_err(|e| e.change_err())
+    }
+}
+
+/// Trait to convert an `Option<T>` into a `Result<T, TError<E>>`.
+pub trait OkOrTError<T>: private::Sealed {
+    /// Convert `None` into a `TError` with `error` as the primary error.
+    ///
+    /// This is shorthand for `.ok_or(error).terror()`.
+    fn ok_or_terror<E>(self, error: E) -> core::result::Result<T, TError<E>>
+    where
+        E: StdError + Send + Sync + 'static;
+}
+
+impl<T> OkOrTError<T> for Option<T> {
+    #[track_caller]
+    fn ok_or_terror<E>(self, error: E) -> core::result::Result<T, TError<E>>
+    where
+        E: StdError + Send + Sync + 'static,
+    {
+        match self {
+            Some(value) => Ok(value),
+            None => Err(TError::new(error)),
+        }
+    }
+}
+
+/// Collect an iterator of results into a `Vec`, stopping at the first error.
+///
+/// The first error encountered is converted into a `TError<E>`.
+///
+/// ```ignore
+/// let values: typederror::Result<Vec<u32>, MyError> =
+///     typederror::try_collect(inputs.iter().map(|s| s.parse::<u32>()));
+/// ```
+pub fn try_collect<I, T, EIn, E>(iter: I) -> Result<Vec<T>, E>
+where
+    I: IntoIterator<Item = core::result::Result<T, EIn>>,
+    EIn: Into<TError<E>>,
+{
+    iter.into_iter().map(|r| r.map_err(Into::into)).collect()
+}
+
+/// Collect an iterator of results into the successful values and a combined error.
+///
+/// Unlike `try_collect()`, this does not stop at the first error. All errors
+/// are converted into `TError<E>` and combined using `TError::combine()`,
+/// so each one keeps its own context. The error is `None` if every result
+/// was successful.
+///
+/// ```ignore
+/// let (values, err) = typederror::partition_results::<_, _, _, MyError>(
+///     inputs.iter().map(|s| s.parse::<u32>()),
+/// );
+/// ```
+pub fn partition_results<I, T, EIn, E>(iter: I) -> (Vec<T>, Option<TError<E>>)
+where
+    I: IntoIterator<Item = core::result::Result<T, EIn>>,
+    EIn: Into<TError<E>>,
+{
+    let mut values = Vec::new();
+    let mut errors = Vec::new();
+    for result in iter {
+        match result {
+            Ok(value) => values.push(value),
+            Err(err) => errors.push(err.into()),
+        }
+    }
+    let error = TError::combine(errors);
+    (values, error)
+}
+
+/// Call `f`, and add `context` to the error if it fails.
+///
+/// The error is converted into a `TError<E>` first. The context is evaluated
+/// eagerly, since it is passed by value, but it is only converted to a
+/// message if `f` fails.
+///
+/// ```ignore
+/// let config = typederror::with_terror("loading config", || {
+///     let s = std::fs::read_to_string(path)?;
+///     parse_config(&s)
+/// })?;
+/// ```
+#[track_caller]
+pub fn with_terror<C, F, T, EIn, E>(context: C, f: F) -> Result<T, E>
+where
+    C: Display + Send + Sync + 'static,
+    F: FnOnce() -> core::result::Result<T, EIn>,
+    EIn: Into<TError<E>>,
+{
+    match f() {
+        Ok(value) => Ok(value),
+        Err(e) => Err(e.into().context(context)),
+    }
+}
+
+/// Call `f`, converting a panic into a `TError`.
+///
+/// This wraps `std::panic::catch_unwind()`. If the panic payload is a `&str`
+/// or `String`, the error message is `"panicked: {message}"`, otherwise it is
+/// just `"panicked"`. The error never contains an error of the primary type E.
+///
+/// Note that the panic hook still runs, so the panic is still reported on
+/// stderr by default.
+///
+/// ```ignore
+/// let value: typederror::Result<u32, MyError> = typederror::catch_unwind(|| plugin.run());
+/// ```
+#[cfg(feature = "std")]
+#[track_caller]
+pub fn catch_unwind<F, T, E>(f: F) -> Result<T, E>
+where
+    F: FnOnce() -> T + std::panic::UnwindSafe,
+{
+    let payload = match std::panic::catch_unwind(f) {
+        Ok(value) => return Ok(value),
+        Err(payload) => payload,
+    };
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str));
+    match message {
+        Some(message) => Err(TError::from_msg(format!("panicked: {message}"))),
+        None => Err(TError::from_msg("panicked")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use std::{format, vec};
+
+    use super::*;
+
+    #[derive(Debug, thiserror::Error)]
+    enum MyError {
+        #[error("something went wrong")]
+        One,
+        #[error("Error two")]
+        Two(Box<dyn StdError + Send + Sync + 'static>),
+        #[error("io error: {0}")]
+        Three(#[from] std::io::Error),
+    }
+
+    impl DefaultError for MyError {
+        fn from_anyhow(err: anyhow::Error) -> Self {
+            MyError::Two(err.into())
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct OtherError;
+
+    impl Display for OtherError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "OtherError")
+        }
+    }
+
+    impl StdError for OtherError {}
+
+    fn do_other_task(fail: bool) -> std::result::Result<(), OtherError> {
+        if fail {
+            Err(OtherError)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn fallible_fn(other: bool) -> std::result::Result<(), TError<MyError>> {
+        do_other_task(other)?;
+
+        Err(MyError::One).context("failed")
+    }
+
+    #[test]
+    fn test_err() {
+        let err = fallible_fn(false).unwrap_err();
+        assert_matches!(err.get_ref(), Some(&MyError::One));
+        assert_eq!(format!("{err}"), "failed");
+
+        let e2 = err
+            .context("add more context")
+            .context("and even more context");
+
+        assert_matches!(e2.get_ref(), Some(&MyError::One));
+
+        let e3 = e2.context(MyError::Two(anyhow::anyhow!("other error").into()));
+        assert_eq!(e3.to_string(), "Error two");
+        assert_matches!(e3.get_ref(), Some(&MyError::One));
+
+        let e4 = e3.cause(MyError::Two(anyhow::anyhow!("other error").into()));
+        assert_matches!(e4.get_ref(), Some(&MyError::Two(_)));
+
+        let e5 = e4.context(MyError::One);
+        assert_eq!(e5.to_string(), "something went wrong");
+        assert_matches!(e5.get_ref(), Some(&MyError::Two(_)));
+
+        let err = fallible_fn(true).unwrap_err();
+        assert_matches!(err.get_ref(), None);
+        assert_eq!(err.downcast_ref(), Some(&OtherError));
+        assert_matches!(err.get(), MyError::Two(_)); // We got some other error.
+    }
+
+    #[derive(Debug, thiserror::Error)]
+    enum FromAnyhowError {
+        #[error("not found")]
+        NotFound,
+        #[error(transparent)]
+        Other(#[from] anyhow::Error),
+    }
+
+    crate::impl_default_error!(FromAnyhowError);
+
+    #[test]
+    fn test_default_error_from_anyhow() {
+        let err: TError<FromAnyhowError> = fallible_fn(true).unwrap_err().change_err();
+        assert_matches!(err.get(), FromAnyhowError::Other(e) if e.is::<OtherError>());
+
+        let err = TError::new(FromAnyhowError::NotFound);
+        assert_matches!(err.get(), FromAnyhowError::NotFound);
+    }
+
+    #[derive(Debug, thiserror::Error)]
+    enum ManualDefaultError {
+        #[error(transparent)]
+        Other(#[from] anyhow::Error),
+        #[error("misc: {0}")]
+        Misc(String),
+    }
+
+    // A type with `From<anyhow::Error>` can still choose its own catch-all.
+    impl DefaultError for ManualDefaultError {
+        fn from_anyhow(err: anyhow::Error) -> Self {
+            Self::Misc(err.to_string())
+        }
+    }
+
+    #[test]
+    fn test_default_error_manual_with_from_anyhow() {
+        let err: TError<ManualDefaultError> = fallible_fn(true).unwrap_err().change_err();
+        assert_matches!(err.get(), ManualDefaultError::Misc(msg) if msg == "OtherError");
+        assert_matches!(
+            ManualDefaultError::from(anyhow::anyhow!("x")),
+            ManualDefaultError::Other(_)
+        );
+    }
+
+    #[test]
+    fn test_option_context() {
+        let map = std::collections::HashMap::from([("a", 1)]);
+        let value: std::result::Result<_, TError<MyError>> = map.get("a").context("missing key");
+        assert_eq!(value.unwrap(), &1);
+
+        let err: TError<MyError> = map
+            .get("b")
+            .with_context(|| format!("missing key: {}", "b"))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "missing key: b");
+        assert_matches!(err.get(), MyError::Two(_));
+    }
+
+    #[test]
+    fn test_context_debug() {
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        struct Request {
+            id: u32,
+        }
+
+        let err: TError<MyError> = Err::<(), _>(MyError::One)
+            .context_debug(Request { id: 7 })
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Request { id: 7 }");
+        assert_matches!(err.get_ref(), Some(MyError::One));
+
+        let err: TError<MyError> = None::<u32>.context_debug(("key", 2)).unwrap_err();
+        assert_eq!(err.to_string(), "(\"key\", 2)");
+    }
+
+    #[test]
+    fn test_context_debug_is_lazy() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        struct Probe;
+
+        impl Debug for Probe {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                CALLS.fetch_add(1, Ordering::SeqCst);
+                write!(f, "Probe")
+            }
+        }
+
+        let value: Result<u32, MyError> = Ok::<_, MyError>(1).context_debug(Probe);
+        assert_eq!(value.unwrap(), 1);
+        let value: Result<u32, MyError> = Some(1).context_debug(Probe);
+        assert_eq!(value.unwrap(), 1);
+        assert_eq!(CALLS.load(Ordering::SeqCst), 0);
+
+        let err: TError<MyError> = Err::<(), _>(MyError::One).context_debug(Probe).unwrap_err();
+        assert_eq!(err.to_string(), "Probe");
+        assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_ok_or_terror() {
+        assert_eq!(Some(1).ok_or_terror(MyError::One).unwrap(), 1);
+
+        let err = None::<u32>.ok_or_terror(MyError::One).unwrap_err();
+        assert_matches!(err.get_ref(), Some(MyError::One));
+    }
+
+    #[test]
+    fn test_note() {
+        let err = fallible_fn(false)
+            .unwrap_err()
+            .note("(see logs for details)")
+            .context("outer");
+        assert_eq!(err.to_string(), "outer (see logs for details)");
+        assert_eq!(
+            format!("{err:#}"),
+            "outer: failed: something went wrong (see logs for details)"
+        );
+        assert!(format!("{err:?}").ends_with("\n\nNote: (see logs for details)"));
+        assert_eq!(
+            err.notes().collect::<Vec<_>>(),
+            vec!["(see logs for details)"]
+        );
+        assert_eq!(err.chain().count(), 3);
+        assert_matches!(err.get_ref(), Some(MyError::One));
+    }
+
+    #[test]
+    fn test_terror_msg() {
+        fn legacy(fail: bool) -> std::result::Result<u32, String> {
+            if fail {
+                Err(format!("legacy failure {}", 1))
+            } else {
+                Ok(1)
+            }
+        }
+
+        assert_eq!(legacy(false).terror_msg::<MyError>().unwrap(), 1);
+
+        let err: TError<MyError> = legacy(true).terror_msg().unwrap_err();
+        assert_eq!(err.to_string(), "legacy failure 1");
+        assert!(err.get_ref().is_none());
+
+        let err: TError<MyError> = Err::<(), _>("static failure").terror_msg().unwrap_err();
+        assert_eq!(err.to_string(), "static failure");
+    }
+
+    #[test]
+    fn test_try_get_cloned() {
+        let err = TError::new(OtherError).context("outer");
+        assert_eq!(err.try_get_cloned(), Some(OtherError));
+        assert_eq!(err.to_string(), "outer");
+
+        let err: TError<OtherError> = TError::from_msg("other");
+        assert_eq!(err.try_get_cloned(), None);
+    }
+
+    #[cfg(feature = "capture-meta")]
+    #[test]
+    fn test_capture_meta() {
+        let before = std::time::SystemTime::now();
+        let err = std::thread::Builder::new()
+            .name("worker".into())
+            .spawn(|| fallible_fn(false).unwrap_err())
+            .unwrap()
+            .join()
+            .unwrap();
+        let err = err.context("outer").change_err::<()>();
+        assert_eq!(err.thread_name(), Some("worker"));
+        assert!(err.created_at() >= before);
+    }
+
+    #[test]
+    fn test_try_from_anyhow() {
+        let error = anyhow::Error::new(MyError::One).context("outer");
+        let err = TError::<MyError>::try_from_anyhow(error).unwrap();
+        assert_matches!(err.get_ref(), Some(MyError::One));
+        assert_eq!(err.to_string(), "outer");
+
+        let error = anyhow::Error::new(OtherError);
+        let error = TError::<MyError>::try_from_anyhow(error).unwrap_err();
+        assert!(error.is::<OtherError>());
+    }
+
+    #[test]
+    fn test_combine() {
+        let errors = vec![
+            fallible_fn(true).unwrap_err(),
+            fallible_fn(false).unwrap_err().context("second"),
+        ];
+        let err = TError::combine(errors).unwrap();
+        assert_matches!(err.get_ref(), Some(&MyError::One));
+        assert_eq!(
+            err.to_string(),
+            "2 errors occurred:\n- OtherError\n- second: failed: something went wrong"
+        );
+
+        let multi = err.downcast_ref::<MultiError>().unwrap();
+        assert_eq!(multi.errors().len(), 2);
+
+        let err = TError::combine(vec![
+            fallible_fn(true).unwrap_err().note("(see logs)"),
+            TError::from_msg("b"),
+        ])
+        .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "2 errors occurred:\n- OtherError (see logs)\n- b"
+        );
+        let multi = err.downcast_ref::<MultiError>().unwrap();
+        assert_eq!(multi.notes(0).collect::<Vec<_>>(), vec!["(see logs)"]);
+        assert_eq!(multi.notes(1).count(), 0);
+
+        let err = TError::combine(vec![fallible_fn(false).unwrap_err()]).unwrap();
+        assert_eq!(err.to_string(), "failed");
+
+        assert!(TError::<MyError>::combine(vec![]).is_none());
+    }
+
+    #[test]
+    fn test_combine_accessors() {
+        let combined = || {
+            TError::combine(vec![
+                fallible_fn(true).unwrap_err(),
+                TError::new(MyError::Two("two".into())).context("second"),
+            ])
+            .unwrap()
+        };
+        let mut err = combined();
+        assert!(err.has_primary());
+        assert_matches!(err.get_mut(), Some(MyError::Two(_)));
+        *err.get_mut().unwrap() = MyError::One;
+        assert_matches!(err.get_ref(), Some(MyError::One));
+
+        assert_matches!(combined().try_get(), Ok(MyError::Two(_)));
+        assert_matches!(combined().get(), MyError::Two(_));
+        assert_matches!(combined().into_primary(), MyError::Two(_));
+        assert_matches!(combined().context("outer").try_get(), Ok(MyError::Two(_)));
+
+        let err = TError::<MyError>::combine(vec![
+            fallible_fn(true).unwrap_err().change_err(),
+            TError::from_msg("other"),
+        ])
+        .unwrap();
+        let err = err.try_get().unwrap_err();
+        assert!(err.is::<MultiError>());
+    }
+
+    #[test]
+    fn test_try_collect() {
+        let values: crate::Result<Vec<u32>, MyError> =
+            try_collect(["1", "2", "3"].iter().map(|s| s.parse::<u32>()));
+        assert_eq!(values.unwrap(), vec![1, 2, 3]);
+
+        let err = try_collect::<_, _, _, MyError>(["1", "x", "y"].iter().map(|s| s.parse::<u32>()))
+            .unwrap_err();
+        assert!(err.is::<std::num::ParseIntError>());
+
+        let values: crate::Result<Vec<()>, MyError> =
+            try_collect([Ok(()), Err(TError::new(MyError::One))]);
+        assert_matches!(values.unwrap_err().get_ref(), Some(&MyError::One));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_catch_unwind() {
+        let value: Result<u32, MyError> = catch_unwind(|| 1);
+        assert_eq!(value.unwrap(), 1);
+
+        let err = catch_unwind::<_, (), MyError>(|| panic!("plugin failed: {}", 42)).unwrap_err();
+        assert_eq!(err.to_string(), "panicked: plugin failed: 42");
+        assert!(err.get_ref().is_none());
+
+        let err = catch_unwind::<_, (), MyError>(|| panic!("static message")).unwrap_err();
+        assert_eq!(err.to_string(), "panicked: static message");
+
+        let err = catch_unwind::<_, (), MyError>(|| std::panic::panic_any(42_u32)).unwrap_err();
+        assert_eq!(err.to_string(), "panicked");
+    }
+
+    #[test]
+    fn test_with_terror() {
+        let value: Result<u32, MyError> = with_terror("parsing", || "1".parse::<u32>());
+        assert_eq!(value.unwrap(), 1);
+
+        let err = with_terror::<_, _, (), _, MyError>("loading config", || fallible_fn(false))
+            .unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "loading config: failed: something went wrong"
+        );
+        assert_matches!(err.get_ref(), Some(MyError::One));
+
+        let err: TError<MyError> =
+            with_terror("parsing", || "x".parse::<u32>().map(|_| ())).unwrap_err();
+        assert_eq!(err.to_string(), "parsing");
+        assert!(err.find_ref::<core::num::ParseIntError>().is_some());
+    }
+
+    #[test]
+    fn test_partition_results() {
+        let (values, err) =
+            partition_results::<_, _, _, MyError>(["1", "x", "3"].iter().map(|s| s.parse::<u32>()));
+        assert_eq!(values, vec![1, 3]);
+        let err = err.unwrap();
+        assert!(err.is::<std::num::ParseIntError>());
+
+        let (values, err) = partition_results([
+            Err(TError::new(MyError::One).context("first")),
+            Ok(2),
+            Err(TError::from_msg("second")),
+        ]);
+        assert_eq!(values, vec![2]);
+        let err = err.unwrap();
+        assert_matches!(err.get_ref(), Some(&MyError::One));
+        assert_eq!(
+            err.to_string(),
+            "2 errors occurred:\n- first: something went wrong\n- second"
+        );
+
+        let (values, err) = partition_results::<_, _, TError<MyError>, _>([Ok(1)]);
+        assert_eq!(values, vec![1]);
+        assert!(err.is_none());
+    }
+
+    impl HttpStatus for MyError {
+        fn status_code(&self) -> u16 {
+            match self {
+                MyError::One => 400,
+                MyError::Two(_) | MyError::Three(_) => 500,
+            }
+        }
+    }
+
+    #[test]
+    fn test_has_primary() {
+        assert!(fallible_fn(false).unwrap_err().has_primary());
+        assert!(!fallible_fn(true).unwrap_err().has_primary());
+    }
+
+    #[test]
+    fn test_debug_assert_primary() {
+        let err = fallible_fn(false).unwrap_err().debug_assert_primary();
+        assert_matches!(err.get_ref(), Some(MyError::One));
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "expected a primary error of type")]
+    fn test_debug_assert_primary_missing() {
+        let _ = fallible_fn(true).unwrap_err().debug_assert_primary();
+    }
+
+    #[test]
+    fn test_escalate_if() {
+        let err = fallible_fn(false).unwrap_err().escalate_if(
+            |e| matches!(e, MyError::One),
+            MyError::Two("escalated".into()),
+        );
+        assert_matches!(err.get_ref(), Some(MyError::Two(_)));
+        assert_eq!(
+            err.chain_strings(),
+            vec!["Error two", "failed", "something went wrong"]
+        );
+
+        let err = fallible_fn(false).unwrap_err().escalate_if(
+            |e| matches!(e, MyError::Two(_)),
+            MyError::Two("escalated".into()),
+        );
+        assert_matches!(err.get_ref(), Some(MyError::One));
+        assert_eq!(err.chain_strings(), vec!["failed", "something went wrong"]);
+
+        let err = fallible_fn(true)
+            .unwrap_err()
+            .escalate_if(|_| true, MyError::Two("escalated".into()));
+        assert!(err.get_ref().is_none());
+    }
+
+    #[test]
+    fn test_merge() {
+        let cleanup: TError<MyError> = TError::from_msg("disk full").context("cleanup failed");
+        let err = fallible_fn(false).unwrap_err().merge(cleanup);
+        assert_eq!(err.to_string(), "failed also: cleanup failed: disk full");
+        assert_eq!(
+            format!("{err:#}"),
+            "failed: something went wrong also: cleanup failed: disk full"
+        );
+        assert!(format!("{err:?}").contains("\n\nNote: also: cleanup failed: disk full"));
+        assert_matches!(err.get_ref(), Some(MyError::One));
+        assert_eq!(err.chain().count(), 2);
+
+        let err = fallible_fn(true)
+            .unwrap_err()
+            .merge(TError::new(MyError::Two("cleanup".into())));
+        assert!(err.get_ref().is_none());
+        assert!(err.to_string().contains("also: Error two"));
+    }
+
+    #[test]
+    fn test_same_kind() {
+        let err = TError::new(MyError::Two("first".into())).context("outer");
+        assert!(err.same_kind(&MyError::Two("second".into())));
+        assert!(!err.same_kind(&MyError::One));
+
+        let err = fallible_fn(true).unwrap_err();
+        assert!(!err.same_kind(&MyError::One));
+    }
+
+    #[test]
+    fn test_try_get_ref() {
+        let err = fallible_fn(false).unwrap_err();
+        assert_matches!(err.try_get_ref(), Ok(&MyError::One));
+
+        let err = fallible_fn(true).unwrap_err();
+        assert_matches!(err.try_get_ref(), Err(e) if e.is::<OtherError>());
+    }
+
+    #[test]
+    fn test_status_code() {
+        let err = fallible_fn(false).unwrap_err();
+        assert_eq!(err.status_code(), Some(400));
+
+        let err = fallible_fn(true).unwrap_err();
+        assert_eq!(err.status_code(), None);
+    }
+
+    impl ErrorCode for MyError {
+        fn code(&self) -> &'static str {
+            match self {
+                MyError::One => "one",
+                MyError::Two(_) => "two",
+                MyError::Three(_) => "io",
+            }
+        }
+    }
+
+    #[test]
+    fn test_code() {
+        let err = fallible_fn(false).unwrap_err().context("more context");
+        assert_eq!(err.code(), Some("one"));
+
+        let err = fallible_fn(true).unwrap_err();
+        assert_eq!(err.code(), None);
+    }
+
+    #[test]
+    fn test_coded() {
+        let err: TError<MyError> = TError::coded("upstream.timeout", "upstream timed out");
+        assert_eq!(err.to_string(), "upstream timed out");
+        assert_eq!(err.code(), Some("upstream.timeout"));
+        assert!(err.get_ref().is_none());
+
+        let err = err.context("calling billing");
+        assert_eq!(err.code(), Some("upstream.timeout"));
+
+        let coded = err.downcast_ref::<CodedError>().unwrap();
+        assert_eq!(coded.code(), "upstream.timeout");
+        assert_eq!(coded.message(), "upstream timed out");
+    }
+
+    #[test]
+    fn test_display_redacted() {
+        #[derive(Debug, thiserror::Error)]
+        #[error("failed to connect to {0}")]
+        struct ConnectError(&'static str);
+
+        impl Redact for ConnectError {
+            fn redacted(&self) -> String {
+                "failed to connect to <redacted>".to_string()
+            }
+        }
+
+        let err = TError::new(ConnectError("postgres://user:secret@db")).context("loading users");
+        assert_eq!(
+            err.display_redacted(),
+            "loading users: failed to connect to <redacted>"
+        );
+        assert_eq!(
+            err.chain().nth(1).unwrap().to_string(),
+            "failed to connect to postgres://user:secret@db"
+        );
+
+        let err: TError<ConnectError> = TError::from_msg("timed out");
+        assert_eq!(err.display_redacted(), "timed out");
+
+        // Errors with and without a primary error are formatted the same way.
+        let redacted = TError::new(ConnectError("postgres://user:secret@db"))
+            .context("loading users")
+            .context("starting up")
+            .note("(retrying)");
+        let plain: TError<ConnectError> = TError::from_msg("failed to connect to <redacted>")
+            .context("loading users")
+            .context("starting up")
+            .note("(retrying)");
+        assert_eq!(
+            redacted.display_redacted(),
+            "starting up: loading users: failed to connect to <redacted> (retrying)"
+        );
+        assert_eq!(plain.display_redacted(), redacted.display_redacted());
+    }
+
+    #[test]
+    fn test_terror() {
         let path = std::path::Path::new("/invalid-dir-doesnt-exist");
         // Using the `.terror()` method, we can convert into `MyError` instead of `std::io::Error`.
         let err: TError<MyError> = std::fs::read_to_string(path).terror().unwrap_err();
         assert_matches!(err.get_ref(), Some(&MyError::Three(_)));
     }
 
+    #[test]
+    fn test_terror_with() {
+        let err: TError<MyError> = do_other_task(true)
+            .terror_with(|e| MyError::Two(e.into()))
+            .unwrap_err();
+        assert_matches!(err.get_ref(), Some(MyError::Two(e)) if e.is::<OtherError>());
+    }
+
     #[test]
     fn test_change_err() {
         let err = fallible_fn(true).unwrap_err();
@@ -323,6 +2561,730 @@ mod tests {
         assert_eq!(err.try_get().unwrap(), OtherError);
     }
 
+    #[test]
+    fn test_new() {
+        let err = TError::new(MyError::One);
+        assert_matches!(err.get_ref(), Some(&MyError::One));
+        assert_eq!(err.to_string(), "something went wrong");
+    }
+
+    #[test]
+    fn test_from_parts() {
+        let err = TError::from_parts(CloneError, OtherError);
+        assert_eq!(err.get_ref(), Some(&CloneError));
+        let chain: Vec<String> = err.chain().map(|e| e.to_string()).collect();
+        assert_eq!(chain, vec!["cloneable", "OtherError"]);
+        assert!(err.find_ref::<OtherError>().is_some());
+    }
+
+    fn unit_fn() -> crate::Result<(), ()> {
+        do_other_task(true).context("unit context")?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_unit_primary() {
+        let err = unit_fn().unwrap_err();
+        assert_eq!(err.to_string(), "unit context");
+        assert_eq!(err.downcast_ref(), Some(&OtherError));
+
+        let err = err.context("more context");
+        assert_eq!(err.root_cause().to_string(), "OtherError");
+        let err: TError<MyError> = err.change_err();
+        assert_matches!(err.get(), MyError::Two(_));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_termination() {
+        use std::process::{ExitCode, Termination};
+
+        let err = fallible_fn(false).unwrap_err().context("more context");
+        let debug = format!("{err:?}");
+        assert!(debug.contains("more context"));
+        assert!(debug.contains("something went wrong"));
+        assert_eq!(err.report(), ExitCode::FAILURE);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_backtrace() {
+        use std::backtrace::BacktraceStatus;
+
+        let err = fallible_fn(false).unwrap_err();
+        assert_matches!(
+            err.backtrace().status(),
+            BacktraceStatus::Captured | BacktraceStatus::Disabled
+        );
+        assert_eq!(
+            err.has_backtrace(),
+            err.backtrace().status() == BacktraceStatus::Captured
+        );
+    }
+
+    #[test]
+    fn test_attach() {
+        #[derive(Debug, PartialEq)]
+        struct RequestId(u32);
+
+        impl Display for RequestId {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(f, "request {}", self.0)
+            }
+        }
+
+        let err = fallible_fn(false)
+            .unwrap_err()
+            .attach(RequestId(42))
+            .context("more context");
+        assert_eq!(err.downcast_ref::<RequestId>(), Some(&RequestId(42)));
+        assert_eq!(
+            format!("{err:#}"),
+            "more context: request 42: failed: something went wrong"
+        );
+        assert_matches!(err.get_ref(), Some(&MyError::One));
+    }
+
+    #[test]
+    fn test_with_primary() {
+        let err = fallible_fn(true)
+            .unwrap_err()
+            .context("ctx")
+            .with_primary(MyError::One);
+        assert_matches!(err.get_ref(), Some(&MyError::One));
+        let chain: Vec<String> = err.chain().map(|e| e.to_string()).collect();
+        assert_eq!(chain, vec!["something went wrong", "ctx", "OtherError"]);
+        assert_eq!(err.contexts().count(), 0);
+    }
+
+    #[test]
+    fn test_with_context_for() {
+        let describe = |e: Option<&MyError>| match e {
+            Some(MyError::One) => "during task one",
+            _ => "unknown",
+        };
+
+        let err = fallible_fn(false).unwrap_err().with_context_for(describe);
+        assert_eq!(err.to_string(), "during task one");
+        assert_matches!(err.get_ref(), Some(&MyError::One));
+
+        let err = fallible_fn(true).unwrap_err().with_context_for(describe);
+        assert_eq!(err.to_string(), "unknown");
+
+        let err = TError::new(MyError::Two("id 7".into())).with_context_for(|e| match e {
+            Some(MyError::Two(inner)) => format!("while handling Two for {inner}"),
+            _ => "unknown".to_string(),
+        });
+        assert_eq!(err.to_string(), "while handling Two for id 7");
+        assert_matches!(err.get_ref(), Some(MyError::Two(_)));
+    }
+
+    #[test]
+    fn test_map_primary() {
+        let err = fallible_fn(false).unwrap_err();
+        let err: TError<OtherError> = err.map_primary(|e| {
+            assert_matches!(e, MyError::One);
+            OtherError
+        });
+        assert_eq!(err.get_ref(), Some(&OtherError));
+
+        let err = fallible_fn(true).unwrap_err();
+        let err: TError<RetryError> = err.map_primary(|_| RetryError(0));
+        assert!(err.get_ref().is_none());
+        assert_eq!(err.downcast_ref(), Some(&OtherError));
+    }
+
+    #[test]
+    fn test_try_map_primary() {
+        let err = fallible_fn(false).unwrap_err();
+        let err: TError<OtherError> = err
+            .try_map_primary(|e| match e {
+                MyError::One => Some(OtherError),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(err.get_ref(), Some(&OtherError));
+
+        let err = TError::new(MyError::One).context("outer");
+        let err = err.try_map_primary::<OtherError, _>(|_| None).unwrap_err();
+        assert_matches!(err.get_ref(), Some(MyError::One));
+        assert_eq!(format!("{err:#}"), "outer: something went wrong");
+        assert_eq!(err.contexts().collect::<Vec<_>>(), vec!["outer"]);
+
+        let err = TError::from_parts(MyError::One, OtherError).context("outer");
+        let err = err.try_map_primary::<OtherError, _>(|_| None).unwrap_err();
+        assert_matches!(err.get_ref(), Some(MyError::One));
+        assert_eq!(
+            format!("{err:#}"),
+            "outer: something went wrong: OtherError"
+        );
+        assert!(err.is::<OtherError>());
+
+        let err = fallible_fn(false)
+            .unwrap_err()
+            .with_primary(MyError::Two("two".into()));
+        let err = err.try_map_primary::<OtherError, _>(|_| None).unwrap_err();
+        assert_matches!(err.get_ref(), Some(MyError::Two(_)));
+        assert_eq!(err.chain().count(), 3);
+
+        let err = fallible_fn(true).unwrap_err();
+        let err = err
+            .try_map_primary::<OtherError, _>(|_| unreachable!())
+            .unwrap_err();
+        assert_eq!(err.downcast_ref(), Some(&OtherError));
+    }
+
+    #[test]
+    fn test_into_boxed() {
+        fn boxed() -> std::result::Result<(), Box<dyn StdError + Send + Sync + 'static>> {
+            fallible_fn(false)?;
+            Ok(())
+        }
+
+        let err = boxed().unwrap_err();
+        assert_eq!(err.to_string(), "failed");
+        assert_eq!(err.source().unwrap().to_string(), "something went wrong");
+    }
+
+    #[test]
+    fn test_and_context() {
+        let err = fallible_fn(false)
+            .unwrap_err()
+            .and_context(|| Ok::<_, String>("user 42"));
+        assert_eq!(err.to_string(), "user 42");
+        assert_matches!(err.get_ref(), Some(MyError::One));
+
+        let err = fallible_fn(false)
+            .unwrap_err()
+            .and_context(|| Err::<String, _>("no such user"));
+        assert_eq!(err.to_string(), "failed to compute context: no such user");
+        assert_matches!(err.get_ref(), Some(MyError::One));
+        assert_eq!(err.contexts().count(), 2);
+    }
+
+    #[test]
+    fn test_convert_err() {
+        #[derive(Debug, thiserror::Error)]
+        #[error("outer: {0}")]
+        struct Outer(MyError);
+
+        impl From<MyError> for Outer {
+            fn from(e: MyError) -> Self {
+                Outer(e)
+            }
+        }
+
+        let err: TError<Outer> = fallible_fn(false).unwrap_err().convert_err();
+        assert_matches!(err.get_ref(), Some(Outer(MyError::One)));
+
+        let err: TError<Outer> = fallible_fn(true).unwrap_err().convert_err();
+        assert!(err.get_ref().is_none());
+        assert_eq!(err.downcast_ref(), Some(&OtherError));
+    }
+
+    #[test]
+    fn test_get_or() {
+        let err = fallible_fn(false).unwrap_err();
+        assert_matches!(
+            err.get_or(MyError::Three(std::io::ErrorKind::Other.into())),
+            MyError::One
+        );
+
+        let err: TError<OtherError> = TError::from_msg("other");
+        assert_eq!(err.get_or(OtherError), OtherError);
+
+        let err = fallible_fn(true).unwrap_err();
+        assert_matches!(err.get_or(MyError::One), MyError::One);
+
+        let err = fallible_fn(true).unwrap_err();
+        let e = err.get_or_else(|e| {
+            assert!(e.is::<OtherError>());
+            MyError::Two(e.into())
+        });
+        assert_matches!(e, MyError::Two(_));
+
+        let err = fallible_fn(false).unwrap_err();
+        assert_matches!(err.get_or_else(|_| unreachable!()), MyError::One);
+    }
+
+    #[test]
+    fn test_into_parts() {
+        let err = fallible_fn(false).unwrap_err().context("outer");
+        let (primary, rest) = err.into_parts();
+        assert_matches!(primary, Some(MyError::One));
+        assert_eq!(format!("{rest:#}"), "outer: failed");
+
+        let (primary, rest) = TError::new(MyError::One).into_parts();
+        assert_matches!(primary, Some(MyError::One));
+        assert_eq!(format!("{rest:#}"), "something went wrong");
+
+        let err = fallible_fn(true).unwrap_err();
+        let (primary, rest) = err.into_parts();
+        assert!(primary.is_none());
+        assert!(rest.is::<OtherError>());
+
+        // A context message with the same text as the primary error is kept.
+        let err = TError::from_parts(MyError::One, OtherError).context("something went wrong");
+        let (primary, rest) = err.into_parts();
+        assert_matches!(primary, Some(MyError::One));
+        assert_eq!(format!("{rest:#}"), "something went wrong: OtherError");
+
+        let err = TError::combine(vec![
+            fallible_fn(true).unwrap_err(),
+            fallible_fn(false).unwrap_err().context("second"),
+        ])
+        .unwrap()
+        .context("outer");
+        assert!(err.has_primary());
+        let (primary, rest) = err.into_parts();
+        assert_matches!(primary, Some(MyError::One));
+        assert_eq!(
+            format!("{rest:#}"),
+            "outer: 2 errors occurred:\n- OtherError\n- second: failed"
+        );
+        let multi = rest.downcast_ref::<MultiError>().unwrap();
+        assert!(multi.errors()[0].is::<OtherError>());
+    }
+
+    #[test]
+    fn test_from_msg() {
+        let name = String::from("config.toml");
+        let err: TError<MyError> = TError::from_msg(&name);
+        assert_eq!(err.to_string(), "config.toml");
+
+        let err: TError<MyError> = TError::from_msg(format!("failed to read {name}"));
+        assert_eq!(err.to_string(), "failed to read config.toml");
+        assert_matches!(err.get(), MyError::Two(_));
+    }
+
+    #[test]
+    fn test_from_boxed() {
+        #[derive(Debug, thiserror::Error)]
+        #[error("wrapper")]
+        struct Wrapper(#[source] OtherError);
+
+        let boxed: Box<dyn StdError + Send + Sync> = Box::new(Wrapper(OtherError));
+        let err: TError<MyError> = TError::from_boxed(boxed).context("ctx");
+        assert_eq!(format!("{err:#}"), "ctx: wrapper: OtherError");
+        assert_eq!(err.find_ref::<OtherError>(), Some(&OtherError));
+        assert_matches!(err.get(), MyError::Two(_));
+
+        let boxed: Box<dyn StdError + Send + Sync> =
+            Box::new(std::io::Error::new(std::io::ErrorKind::NotFound, "missing"));
+        let err: TError<MyError> = TError::from_boxed(boxed).context("ctx");
+        let boxed = err
+            .downcast_ref::<Box<dyn StdError + Send + Sync>>()
+            .unwrap();
+        assert_eq!(
+            boxed.downcast_ref::<std::io::Error>().map(|e| e.kind()),
+            Some(std::io::ErrorKind::NotFound)
+        );
+        assert_eq!(
+            err.find_ref::<std::io::Error>().map(|e| e.kind()),
+            Some(std::io::ErrorKind::NotFound)
+        );
+    }
+
+    #[derive(Debug, Clone, PartialEq, thiserror::Error)]
+    #[error("cloneable")]
+    struct CloneError;
+
+    #[test]
+    fn test_clone() {
+        let err = TError::new(CloneError).context("one").context("two");
+        let cloned = err.clone();
+        assert_eq!(cloned.get_ref(), Some(&CloneError));
+        assert_eq!(format!("{cloned:#}"), format!("{err:#}"));
+
+        let err: TError<CloneError> = fallible_fn(true).unwrap_err().change_err().context("ctx");
+        let cloned = err.clone();
+        assert_eq!(format!("{cloned:#}"), "ctx: OtherError");
+        assert!(cloned.get_ref().is_none());
+        assert!(!cloned.is::<OtherError>());
+
+        let err = TError::from_parts(CloneError, OtherError).context("ctx");
+        let cloned = err.clone();
+        assert_eq!(cloned.get_ref(), Some(&CloneError));
+        assert_eq!(format!("{cloned:#}"), "ctx: cloneable: OtherError");
+        assert!(cloned == err);
+
+        let err = fallible_fn(false)
+            .unwrap_err()
+            .change_err()
+            .with_primary(CloneError)
+            .context("ctx");
+        let cloned = err.clone();
+        assert_eq!(cloned.get_ref(), Some(&CloneError));
+        assert_eq!(format!("{cloned:#}"), format!("{err:#}"));
+        assert!(cloned == err);
+    }
+
+    #[test]
+    fn test_partial_eq() {
+        let err: TError<OtherError> = fallible_fn(true).unwrap_err().change_err();
+        assert_eq!(err, TError::from(OtherError));
+        assert_eq!(err.context("ctx"), TError::new(OtherError));
+
+        let err: TError<OtherError> = TError::from_msg("msg").context("ctx");
+        assert_eq!(err, TError::from_msg("msg").context("ctx"));
+        assert_ne!(err, TError::from_msg("ctx"));
+        assert_ne!(err, TError::new(OtherError));
+    }
+
+    #[test]
+    // The lazily captured backtrace inside `anyhow::Error` does not affect the hash.
+    #[allow(clippy::mutable_key_type)]
+    fn test_hash() {
+        use std::collections::HashSet;
+
+        #[derive(Debug, PartialEq, Eq, Hash, thiserror::Error)]
+        enum Kind {
+            #[error("not found: {0}")]
+            NotFound(u32),
+        }
+
+        let errors: HashSet<TError<Kind>> = [
+            TError::new(Kind::NotFound(1)),
+            TError::new(Kind::NotFound(1)).context("ctx"),
+            TError::new(Kind::NotFound(2)),
+            TError::from_msg("msg").context("ctx"),
+            TError::from_msg("msg").context("ctx"),
+            TError::from_msg("not found: 1"),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(errors.len(), 4);
+    }
+
+    #[test]
+    fn test_anyhow_round_trip() {
+        let terr = fallible_fn(false).unwrap_err().context("outer");
+        let a: anyhow::Error = terr.into();
+        let b = TError::<MyError>::from_anyhow(a);
+        assert_matches!(b.get_ref(), Some(MyError::One));
+        assert_eq!(format!("{b:#}"), "outer: failed: something went wrong");
+
+        let terr = TError::new(MyError::One).cause(OtherError);
+        let a: anyhow::Error = terr.into();
+        let b = TError::<OtherError>::from_anyhow(a);
+        assert_eq!(b.get_ref(), Some(&OtherError));
+        assert!(b.change_err::<MyError>().has_primary());
+    }
+
+    #[test]
+    fn test_into_primary() {
+        assert_matches!(fallible_fn(false).unwrap_err().into_primary(), MyError::One);
+        assert_matches!(
+            fallible_fn(true).unwrap_err().into_primary(),
+            MyError::Two(_)
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        let err = fallible_fn(false).unwrap_err().normalize();
+        assert_matches!(err.get_ref(), Some(&MyError::One));
+        assert_eq!(err.to_string(), "failed");
+
+        let err = fallible_fn(true).unwrap_err().normalize();
+        assert_matches!(err.get_ref(), Some(MyError::Two(e)) if e.to_string() == "OtherError");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_into_io_error() {
+        use std::io::{Error, ErrorKind};
+
+        let err: TError<MyError> = Error::new(ErrorKind::NotFound, "missing").into();
+        let io = err.into_io_error();
+        assert_eq!(io.kind(), ErrorKind::NotFound);
+        assert_eq!(io.to_string(), "missing");
+
+        let err: TError<MyError> = Err::<(), _>(Error::new(ErrorKind::NotFound, "missing"))
+            .context("failed to read")
+            .unwrap_err();
+        let io = err.into_io_error();
+        assert_eq!(io.kind(), ErrorKind::NotFound);
+        assert_eq!(io.to_string(), "failed to read");
+        assert_eq!(
+            io.get_ref().unwrap().source().unwrap().to_string(),
+            "missing"
+        );
+
+        let io = fallible_fn(false).unwrap_err().into_io_error();
+        assert_eq!(io.kind(), ErrorKind::Other);
+        assert_eq!(io.to_string(), "failed");
+    }
+
+    #[test]
+    fn test_anyhow_accessors() {
+        let err = fallible_fn(false).unwrap_err();
+        assert_eq!(err.as_anyhow().to_string(), "failed");
+        let err = err.into_anyhow();
+        assert_matches!(err.downcast_ref::<MyError>(), Some(&MyError::One));
+    }
+
+    #[test]
+    fn test_deref() {
+        let err = fallible_fn(false).unwrap_err();
+        let anyhow_err: &anyhow::Error = &err;
+        assert_eq!(anyhow_err.to_string(), "failed");
+        assert!(core::ptr::eq(&*err, err.as_anyhow()));
+    }
+
+    #[test]
+    fn test_primary_type_name() {
+        let err = fallible_fn(true).unwrap_err();
+        assert!(err.primary_type_name().ends_with("MyError"));
+        assert_eq!(err.change_err::<()>().primary_type_name(), "()");
+    }
+
+    #[test]
+    fn test_is() {
+        let err = fallible_fn(false).unwrap_err();
+        assert!(err.is::<MyError>());
+        assert!(!err.is::<OtherError>());
+
+        let err = fallible_fn(true).unwrap_err();
+        assert!(err.is::<OtherError>());
+        assert!(!err.is::<MyError>());
+    }
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("attempts: {0}")]
+    struct RetryError(u32);
+
+    #[test]
+    fn test_get_mut() {
+        let mut err: TError<RetryError> = RetryError(1).into();
+        if let Some(e) = err.get_mut() {
+            e.0 += 1;
+        }
+        assert_eq!(err.get_ref().unwrap().0, 2);
+
+        let mut err = err.context("retrying");
+        err.downcast_mut::<RetryError>().unwrap().0 += 1;
+        assert_eq!(err.to_string(), "retrying");
+        assert_eq!(err.downcast_ref::<RetryError>().unwrap().0, 3);
+        assert!(err.downcast_mut::<OtherError>().is_none());
+    }
+
+    #[test]
+    fn test_chain() {
+        let err = fallible_fn(false).unwrap_err().context("more context");
+        let chain: Vec<String> = err.chain().map(|e| e.to_string()).collect();
+        assert_eq!(
+            chain,
+            vec!["more context", "failed", "something went wrong"]
+        );
+        assert_eq!(err.chain_strings(), chain);
+    }
+
+    struct DisplayOnly;
+
+    impl Display for DisplayOnly {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "display only")
+        }
+    }
+
+    #[test]
+    fn test_display_context() {
+        let err: TError<MyError> = Err::<(), _>(DisplayOnly)
+            .display_context("failed")
+            .unwrap_err();
+        assert_eq!(format!("{err:#}"), "failed: display only");
+        assert_matches!(err.get(), MyError::Two(_));
+
+        let err: TError<MyError> = Err::<(), _>(DisplayOnly)
+            .with_display_context(|| format!("failed {}", 2))
+            .unwrap_err();
+        assert_eq!(err.contexts().collect::<Vec<_>>(), vec!["failed 2"]);
+    }
+
+    #[test]
+    fn test_contexts() {
+        let err = fallible_fn(false).unwrap_err().context("more context");
+        let contexts: Vec<String> = err.contexts().collect();
+        assert_eq!(contexts, vec!["more context", "failed"]);
+
+        let err: TError<MyError> = TError::from_anyhow(anyhow::anyhow!("root").context("inner"));
+        assert_eq!(err.contexts().count(), 0);
+        let err = err.context("outer");
+        assert_eq!(err.contexts().collect::<Vec<_>>(), vec!["outer"]);
+    }
+
+    #[test]
+    fn test_find_ref() {
+        #[derive(Debug, thiserror::Error)]
+        #[error("wrapper")]
+        struct Wrapper(#[source] OtherError);
+
+        let err: TError<MyError> = Err::<(), _>(Wrapper(OtherError))
+            .context("more context")
+            .unwrap_err();
+        assert!(err.downcast_ref::<OtherError>().is_none());
+        assert_eq!(err.find_ref::<OtherError>(), Some(&OtherError));
+        assert!(err.find_ref::<Wrapper>().is_some());
+        assert!(err.find_ref::<MyError>().is_none());
+
+        let err: TError<OtherError> = TError::from_parts(OtherError, Wrapper(OtherError));
+        assert!(err.find_ref::<Wrapper>().is_some());
+        let err = TError::from_parts(MyError::Two("two".into()), OtherError).context("ctx");
+        assert_matches!(err.find_ref::<MyError>(), Some(MyError::Two(_)));
+        assert_eq!(err.find_ref::<OtherError>(), Some(&OtherError));
+    }
+
+    #[test]
+    fn test_context_locations() {
+        let (err, line) = (fallible_fn(false).unwrap_err().context("more"), line!());
+        let locations: Vec<_> = err.context_locations().collect();
+        if cfg!(feature = "location") {
+            assert_eq!(locations.len(), 2);
+            assert_eq!(locations[0].line(), line);
+            assert_eq!(locations[0].file(), file!());
+            assert!(locations[1].line() < line);
+        } else {
+            assert!(locations.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_size() {
+        // Notes, context locations and metadata are boxed, so that
+        // `Result<T, TError<E>>` stays small on the happy path.
+        let words = if cfg!(feature = "location") { 4 } else { 3 };
+        assert_eq!(
+            core::mem::size_of::<TError>(),
+            words * core::mem::size_of::<usize>()
+        );
+        let err = fallible_fn(false).unwrap_err().context("more");
+        assert_eq!(
+            err.extra.is_some(),
+            cfg!(feature = "capture-meta") || cfg!(feature = "location")
+        );
+        let err = err.note("(note)");
+        assert_eq!(err.notes().collect::<Vec<_>>(), vec!["(note)"]);
+    }
+
+    #[test]
+    fn test_display_chain() {
+        let err = fallible_fn(false).unwrap_err().context("more context");
+        assert_eq!(
+            err.display_chain().to_string(),
+            "more context: failed: something went wrong"
+        );
+        assert_eq!(format!("{}", err.display_chain()), format!("{err:#}"));
+    }
+
+    #[test]
+    fn test_format() {
+        let err = fallible_fn(false)
+            .unwrap_err()
+            .context("more context")
+            .note("(see logs)");
+        assert_eq!(err.format(&FormatOptions::default()), format!("{err:#}"));
+        assert_eq!(
+            err.format(&FormatOptions::new().separator(" <- ")),
+            "more context <- failed <- something went wrong (see logs)"
+        );
+        assert_eq!(
+            err.format(&FormatOptions::new().include_chain(false)),
+            err.to_string()
+        );
+
+        let with_backtrace = err.format(&FormatOptions::new().include_backtrace(true));
+        #[cfg(feature = "std")]
+        assert_eq!(
+            with_backtrace.contains("Stack backtrace:"),
+            err.has_backtrace()
+        );
+        assert!(with_backtrace.starts_with("more context: failed"));
+    }
+
+    #[test]
+    fn test_root_cause() {
+        let err = fallible_fn(false)
+            .unwrap_err()
+            .context("more context")
+            .context("even more context");
+        assert_eq!(err.root_cause().to_string(), "something went wrong");
+    }
+
+    #[test]
+    fn test_into_root() {
+        let err = fallible_fn(false)
+            .unwrap_err()
+            .context("more context")
+            .into_root();
+        assert_eq!(format!("{err:#}"), "something went wrong");
+        assert_matches!(err.get_ref(), Some(MyError::One));
+        assert_eq!(err.contexts().count(), 0);
+
+        let err = fallible_fn(true).unwrap_err().context("outer").into_root();
+        assert_eq!(format!("{err:#}"), "OtherError");
+        assert!(err.get_ref().is_none());
+
+        let err = TError::new(MyError::One)
+            .with_primary(MyError::Two("two".into()))
+            .into_root();
+        assert_eq!(format!("{err:#}"), "something went wrong");
+        assert!(err.get_ref().is_none());
+    }
+
+    #[test]
+    fn test_source() {
+        let err = fallible_fn(false).unwrap_err();
+        assert_eq!(err.source().unwrap().to_string(), "something went wrong");
+
+        let dyn_err: &dyn StdError = err.as_ref();
+        assert_eq!(dyn_err.to_string(), "failed");
+        assert!(dyn_err.source().is_some());
+    }
+
+    #[test]
+    fn test_prelude() {
+        use crate::prelude::*;
+
+        fn inner() -> TEResult<(), MyError> {
+            do_other_task(true).context("inner")?;
+            Ok(())
+        }
+
+        let result: TEResult<(), OtherError> = inner().change_err();
+        let err = result.unwrap_err();
+        assert_eq!(err.to_string(), "inner");
+        assert_eq!(err.get_ref(), Some(&OtherError));
+    }
+
+    #[test]
+    fn test_and_then_terror() {
+        let parse = |s: &str| s.parse::<u32>().map_err(|_| MyError::One);
+
+        let value: crate::Result<u32, MyError> = Ok("42").and_then_terror(parse);
+        assert_eq!(value.unwrap(), 42);
+
+        let err = Ok("x").and_then_terror(parse).unwrap_err();
+        assert_matches!(err.get_ref(), Some(&MyError::One));
+
+        let err = fallible_fn(true)
+            .and_then_terror(|_| Ok::<_, MyError>(1))
+            .unwrap_err();
+        assert!(err.is::<OtherError>());
+    }
+
+    #[test]
+    fn test_erase_err() {
+        fn erased() -> crate::Result<(), ()> {
+            fallible_fn(false).erase_err()?;
+            Ok(())
+        }
+
+        let err = erased().unwrap_err();
+        assert_eq!(err.to_string(), "failed");
+        assert!(err.is::<MyError>());
+    }
+
     #[test]
     fn test_change_err_result() {
         let err = fallible_fn(true);
@@ -0,0 +1,94 @@
+use core::fmt::{Debug, Display};
+use std::error::Error as StdError;
+
+use crate::TError;
+
+/// Wrapper that allows an `anyhow::Error` to be stored in an `eyre::Report`.
+///
+/// This lets `TError::from_eyre()` recover the original error, including the
+/// primary error, when a `TError` is converted to a `Report` and back again.
+struct AnyhowError(anyhow::Error);
+
+impl Debug for AnyhowError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl Display for AnyhowError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl StdError for AnyhowError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.0.source()
+    }
+}
+
+/// Convert a `TError` into an `eyre::Report`.
+///
+/// The `Display` output and the chain of sources are preserved. The errors
+/// in the chain cannot be downcast from the `Report` directly, but converting
+/// it back with `TError::from_eyre()` restores the original error.
+impl<E> From<TError<E>> for eyre::Report {
+    fn from(err: TError<E>) -> Self {
+        eyre::Report::new(AnyhowError(err.into_anyhow()))
+    }
+}
+
+impl<E> TError<E> {
+    /// Construct a new `TError` from an `eyre::Report`.
+    ///
+    /// If the report was created from a `TError`, the original error is
+    /// restored, so `get_ref()` returns the primary error if it is present.
+    /// Otherwise the `Display` output and the chain of sources of the report
+    /// are preserved, but the errors in it can only be found via `find_ref()`.
+    #[track_caller]
+    pub fn from_eyre(report: eyre::Report) -> Self {
+        match report.downcast::<AnyhowError>() {
+            Ok(error) => TError::from_anyhow(error.0),
+            Err(report) => TError::from_boxed(report.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use crate::TError;
+
+    #[derive(Debug, thiserror::Error)]
+    enum MyError {
+        #[error("not found")]
+        NotFound,
+        #[error("io error")]
+        Io(#[source] std::io::Error),
+    }
+
+    #[test]
+    fn test_eyre_round_trip() {
+        let err = TError::new(MyError::NotFound).context("lookup failed");
+        let report = eyre::Report::from(err);
+        assert_eq!(format!("{report:#}"), "lookup failed: not found");
+
+        let err: TError<MyError> = TError::from_eyre(report);
+        assert_matches!(err.get_ref(), Some(&MyError::NotFound));
+        assert_eq!(format!("{err:#}"), "lookup failed: not found");
+    }
+
+    #[test]
+    fn test_from_eyre() {
+        use eyre::WrapErr;
+
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+        let report = Err::<(), _>(MyError::Io(io))
+            .wrap_err("loading config")
+            .unwrap_err();
+        let err: TError<MyError> = TError::from_eyre(report);
+        assert_eq!(format!("{err:#}"), "loading config: io error: missing");
+        assert!(err.find_ref::<std::io::Error>().is_some());
+    }
+}
@@ -52,6 +52,20 @@
 //! }
 //! ```
 //!
+//! With the `derive` feature enabled, you can derive `DefaultError` instead
+//! and mark the catch-all variant with `#[default_error]`.
+//!
+//! ```ignore
+//! #[derive(Debug, thiserror::Error, typederror::DefaultError)]
+//! enum MyError {
+//!    #[error("IO error: {0}")]
+//!    IoError(#[from] std::io::Error),
+//!    #[error("{0}")]
+//!    #[default_error]
+//!    Misc(typederror::anyhow::Error)
+//! }
+//! ```
+//!
 //! ### Downcasting to the primary error type
 //!
 //! Since `TError` already knows the primary error type, it can provide
@@ -157,17 +171,124 @@
 //! let s = std::fs::read_to_string("file.txt").terror()?;
 //! ```
 //!
+//! ## `no_std` support
+//!
+//! This crate can be used without `std` (but with `alloc`) by disabling the
+//! default `std` feature. `TError::backtrace()`, the `Termination` impl and
+//! the `tracing` feature are only available with `std`.
+//!
+//! ## Minimum Supported Rust Version
+//!
+//! The MSRV is 1.81, since the bounds use `core::error::Error` with or
+//! without `std`.
+//!
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+#[cfg(all(test, not(feature = "std")))]
+extern crate std;
+
 mod error;
 pub use error::*;
 pub mod macros;
+mod multi;
+pub use multi::MultiError;
+pub mod retry;
+pub use retry::retry;
+#[cfg(feature = "actix")]
+mod actix_compat;
+#[cfg(feature = "axum")]
+mod axum_compat;
+#[cfg(feature = "miette")]
+mod diagnostic;
+#[cfg(feature = "eyre")]
+mod eyre_compat;
+#[cfg(feature = "miette")]
+pub use diagnostic::TDiagnostic;
+#[cfg(feature = "log")]
+mod logging;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(feature = "tracing")]
+mod trace;
+
+#[cfg(feature = "derive")]
+pub use typederror_derive::DefaultError;
 
 pub mod prelude {
-    pub use crate::error::{Context, DefaultError, IntoTError, TError, WrapTError};
-    pub use crate::terror;
+    pub use crate::error::{
+        AndThenTError, Context, DefaultError, DisplayContext, EraseTError, IntoTError, MapTError,
+        MsgTError, OkOrTError, TError, WrapTError,
+    };
+    #[cfg(feature = "log")]
+    pub use crate::logging::LogTError;
     pub use crate::Result as TEResult;
+    pub use crate::{bail, bail_with, ensure, terror, terror_typed};
 }
 
-/// Re-export of anyhow macros.
+/// Equivalent to `Ok::<_, TError<E>>(value)`.
+///
+/// This simplifies creating an `Ok` value where the error type cannot be
+/// inferred, such as at the end of a closure or async block that uses `?`.
+/// It mirrors `anyhow::Ok`.
+///
+/// ```ignore
+/// let result = std::thread::spawn(|| {
+///     let config = load_config()?;
+///     typederror::Ok::<_, MyError>(config)
+/// });
+/// ```
+///
+/// Note that this is not part of the prelude, since it would shadow `Ok`.
+#[allow(non_snake_case)]
+pub fn Ok<T, E>(value: T) -> Result<T, E> {
+    core::result::Result::Ok(value)
+}
+
+/// Re-export of anyhow macros and the underlying error type.
+///
+/// Note that `anyhow!` produces an `anyhow::Error` rather than a `TError`.
+/// Use `terror!` to construct a `TError` instead.
+///
+/// `anyhow::bail!` is re-exported as `anyhow_bail!`, so that glob-importing
+/// both this module and the prelude does not make `bail!` ambiguous.
+/// The `bail!` macro in the prelude always returns a `TError`.
 pub mod anyhow {
-    pub use anyhow::{anyhow, bail};
+    pub use anyhow::{anyhow, bail as anyhow_bail, Error};
+}
+
+#[cfg(test)]
+mod tests {
+    use std::string::ToString;
+
+    use crate::{Context, TError};
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("not found")]
+    struct NotFound;
+
+    #[test]
+    fn test_ok() {
+        let f = || {
+            let value: u32 = "1".parse().context("parsing")?;
+            crate::Ok::<_, NotFound>(value)
+        };
+        let value = f().unwrap();
+        assert_eq!(value, 1);
+
+        let f = || {
+            Err(NotFound).context("lookup")?;
+            crate::Ok(())
+        };
+        let err: TError<NotFound> = f().unwrap_err();
+        assert_eq!(err.to_string(), "lookup");
+    }
+
+    #[test]
+    fn test_anyhow_bail() {
+        fn f() -> Result<(), crate::anyhow::Error> {
+            crate::anyhow::anyhow_bail!("failed: {}", 1);
+        }
+        assert_eq!(f().unwrap_err().to_string(), "failed: 1");
+    }
 }
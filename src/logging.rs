@@ -0,0 +1,91 @@
+use crate::error::private;
+use crate::TError;
+
+/// Extension trait to log the error of a `Result<T, TError<E>>` and pass it
+/// through unchanged.
+///
+/// The whole chain of errors is logged on one line, in the same format as
+/// `TError::display_chain()`.
+///
+/// ```ignore
+/// let config = load_config().log_err()?;
+/// ```
+pub trait LogTError<T, E>: private::Sealed {
+    /// Log the error, if any, at the `Error` level.
+    fn log_err(self) -> core::result::Result<T, TError<E>>;
+
+    /// Log the error, if any, at the given level.
+    fn log_err_at(self, level: log::Level) -> core::result::Result<T, TError<E>>;
+}
+
+impl<T, E> LogTError<T, E> for core::result::Result<T, TError<E>> {
+    fn log_err(self) -> core::result::Result<T, TError<E>> {
+        self.log_err_at(log::Level::Error)
+    }
+
+    fn log_err_at(self, level: log::Level) -> core::result::Result<T, TError<E>> {
+        if let Err(e) = &self {
+            log::log!(level, "{}", e.display_chain());
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::string::{String, ToString};
+    use std::sync::Mutex;
+    use std::vec::Vec;
+
+    use log::{Level, Log, Metadata, Record};
+
+    use super::LogTError;
+    use crate::{Context, TError};
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("not found")]
+    struct NotFound;
+
+    /// A logger that records the level and message of each record.
+    struct Recorder(Mutex<Vec<(Level, String)>>);
+
+    impl Log for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record<'_>) {
+            let message = record.args().to_string();
+            self.0.lock().unwrap().push((record.level(), message));
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: Recorder = Recorder(Mutex::new(Vec::new()));
+
+    #[test]
+    fn test_log_err() {
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+
+        let result: crate::Result<(), NotFound> = Err(NotFound).context("lookup failed");
+        let err = result.log_err().unwrap_err();
+        assert!(err.get_ref().is_some());
+
+        let result: crate::Result<(), NotFound> = Err(TError::new(NotFound));
+        let _ = result.log_err_at(Level::Warn);
+
+        let result: crate::Result<u32, NotFound> = Ok(1);
+        assert_eq!(result.log_err().unwrap(), 1);
+
+        let records = LOGGER.0.lock().unwrap();
+        assert_eq!(
+            *records,
+            [
+                (Level::Error, "lookup failed: not found".to_string()),
+                (Level::Warn, "not found".to_string()),
+            ]
+        );
+    }
+}
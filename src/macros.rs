@@ -1,13 +1,291 @@
+/// Construct a `TError` from a message, an existing error, or a format string.
+///
+/// A source error can be given after a `;`, in which case the message is
+/// displayed on top of it and `chain()` continues with the source.
+///
+/// ```ignore
+/// let err = terror!("failed to load {}", path; source = io_err);
+/// ```
 #[macro_export]
 macro_rules! terror {
+    ($fmt:literal $(, $arg:expr)* ; source = $source:expr $(,)?) => {
+        $crate::TError::from($source).context($crate::macros::__private::format!($fmt $(, $arg)*))
+    };
     ($msg:literal $(,)?) => {
         $crate::TError::from_anyhow($crate::anyhow::anyhow!($msg))
     };
+    ($err:expr $(,)?) => ({
+        #[allow(unused_imports)]
+        use $crate::macros::__private::{FlattenKind as _, WrapKind as _};
+        match $err {
+            error => (&error)
+                .terror_kind()
+                .into_terror(error, |error| $crate::anyhow::anyhow!(error)),
+        }
+    });
+    ($fmt:expr, $($arg:tt)*) => {
+        $crate::TError::from_anyhow($crate::anyhow::anyhow!($fmt, $($arg)*))
+    };
+}
+
+/// Implementation details of the `terror!` macro.
+///
+/// If `terror!` is given an existing `TError`, it is passed through with only
+/// the primary error type changed, rather than being wrapped as a new error.
+/// Wrapping it would hide the original error from `get_ref()` and
+/// `downcast_ref()`. The choice is made at compile time using autoref
+/// specialization, the same way `anyhow!` chooses how to wrap its argument.
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::format;
+
+    use crate::TError;
+
+    pub struct Flatten;
+    pub struct Wrap;
+
+    pub trait FlattenKind {
+        fn terror_kind(&self) -> Flatten {
+            Flatten
+        }
+    }
+
+    impl<E> FlattenKind for TError<E> {}
+
+    pub trait WrapKind {
+        fn terror_kind(&self) -> Wrap {
+            Wrap
+        }
+    }
+
+    impl<T: ?Sized> WrapKind for &T {}
+
+    /// Call `f` with `value`, so that the closure argument type of each
+    /// `match_terror!` arm is inferred from the value rather than the body.
+    pub fn call<T, R, F>(value: T, f: F) -> R
+    where
+        F: FnOnce(T) -> R,
+    {
+        f(value)
+    }
+
+    impl Flatten {
+        pub fn into_terror<E, X, F>(self, error: TError<E>, _wrap: F) -> TError<X>
+        where
+            F: FnOnce(TError<E>) -> anyhow::Error,
+        {
+            error.change_err()
+        }
+    }
+
+    impl Wrap {
+        #[track_caller]
+        pub fn into_terror<T, X, F>(self, error: T, wrap: F) -> TError<X>
+        where
+            F: FnOnce(T) -> anyhow::Error,
+        {
+            TError::from_anyhow(wrap(error))
+        }
+    }
+}
+
+/// Construct a `TError` with the given primary error, and optionally
+/// a formatted context message on top of it.
+///
+/// ```ignore
+/// let err = terror_typed!(MyError::NotFound, "failed to load {}", path);
+/// assert_matches!(err.get_ref(), Some(&MyError::NotFound));
+/// ```
+#[macro_export]
+macro_rules! terror_typed {
+    ($err:expr $(,)?) => {
+        $crate::TError::new($err)
+    };
+    ($err:expr, $($arg:tt)+) => {
+        $crate::TError::new($err).context($crate::macros::__private::format!($($arg)+))
+    };
+}
+
+/// Try downcasting an error to several types in order, and call the handler
+/// for the first type that matches.
+///
+/// Each handler receives a reference to the matching error. The final `_` arm
+/// is required, and receives the original error itself if no type matched.
+///
+/// ```ignore
+/// let msg = match_terror!(err, {
+///     std::io::Error => |e| format!("io error: {}", e.kind()),
+///     ParseIntError => |e| format!("parse error: {e}"),
+///     _ => |err| format!("other error: {err}"),
+/// });
+/// ```
+#[macro_export]
+macro_rules! match_terror {
+    ($err:expr, { $($arms:tt)* }) => {
+        match $err {
+            error => $crate::match_terror!(@arms error; $($arms)*),
+        }
+    };
+    (@arms $error:ident; _ => $fallback:expr $(,)?) => {
+        $crate::macros::__private::call($error, $fallback)
+    };
+    (@arms $error:ident; $ty:ty => $handler:expr, $($rest:tt)*) => {
+        if let Some(e) = $error.downcast_ref::<$ty>() {
+            $crate::macros::__private::call(e, $handler)
+        } else {
+            $crate::match_terror!(@arms $error; $($rest)*)
+        }
+    };
+}
+
+/// Returns true if the primary error of a `TError` is present and matches
+/// the given pattern.
+///
+/// This is shorthand for `matches!(err.get_ref(), Some(pattern))`.
+///
+/// ```ignore
+/// if is_variant!(err, MyError::NotFound) { ... }
+/// if is_variant!(err, MyError::Io(..)) { ... }
+/// ```
+#[macro_export]
+macro_rules! is_variant {
+    ($err:expr, $pat:pat $(if $guard:expr)? $(,)?) => {
+        matches!(($err).get_ref(), Some($pat) $(if $guard)?)
+    };
+}
+
+/// Implement `DefaultError` for types that implement `From<anyhow::Error>`.
+///
+/// This is useful for an enum with a `#[from] anyhow::Error` variant,
+/// which then catches any error that is not of the primary type.
+///
+/// ```ignore
+/// #[derive(Debug, thiserror::Error)]
+/// enum MyError {
+///     #[error("not found")]
+///     NotFound,
+///     #[error(transparent)]
+///     Other(#[from] typederror::anyhow::Error),
+/// }
+///
+/// impl_default_error!(MyError);
+/// ```
+#[macro_export]
+macro_rules! impl_default_error {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl $crate::DefaultError for $ty {
+                fn from_anyhow(err: $crate::anyhow::Error) -> Self {
+                    ::core::convert::From::from(err)
+                }
+            }
+        )+
+    };
+}
+
+/// Define a primary error enum with a catch-all variant.
+///
+/// The enum derives `Debug` and `thiserror::Error`, so the calling crate must
+/// depend on `thiserror`. A catch-all variant containing an `anyhow::Error`
+/// is added, along with a `DefaultError` impl that converts any other error
+/// into it. Otherwise this is a plain enum, so more attributes and impls can
+/// be added as usual.
+///
+/// ```ignore
+/// define_error! {
+///     pub enum MyError {
+///         #[error("not found: {0}")]
+///         NotFound(String),
+///         #[error("io error: {0}")]
+///         Io(#[from] std::io::Error),
+///     }
+///     catch_all = Other;
+/// }
+/// ```
+#[macro_export]
+macro_rules! define_error {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident { $($variants:tt)* }
+        catch_all = $catch_all:ident $(;)?
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, thiserror::Error)]
+        $vis enum $name {
+            #[error("{0}")]
+            $catch_all($crate::anyhow::Error),
+            $($variants)*
+        }
+
+        impl $crate::DefaultError for $name {
+            fn from_anyhow(err: $crate::anyhow::Error) -> Self {
+                Self::$catch_all(err)
+            }
+        }
+    };
+}
+
+/// Unwrap the error of a `Result<T, TError<E>>` in a test, and assert that
+/// its primary error matches the given pattern.
+///
+/// Without `=>`, the primary error is returned. With `=>`, the expression
+/// after it is returned, which can use the bindings from the pattern.
+///
+/// # Panics
+///
+/// Panics if the result is `Ok`, if no error of type E is present, or if
+/// the error does not match the pattern. The message includes the actual error.
+///
+/// ```ignore
+/// let id = expect_terror!(find_user(42), MyError::NotFound(id) => id);
+/// let err = expect_terror!(find_user(42), MyError::NotFound(_));
+/// ```
+#[cfg(feature = "test-util")]
+#[macro_export]
+macro_rules! expect_terror {
+    ($result:expr, $pat:pat $(if $guard:expr)? => $out:expr $(,)?) => {
+        $crate::expect_terror!(@unwrap $result, stringify!($pat), match {
+            ::core::result::Result::Ok($pat) $(if $guard)? => $out,
+        })
+    };
+    ($result:expr, $pat:pat $(if $guard:expr)? $(,)?) => {
+        $crate::expect_terror!(@unwrap $result, stringify!($pat), match {
+            ::core::result::Result::Ok(error) if matches!(error, $pat $(if $guard)?) => error,
+        })
+    };
+    (@unwrap $result:expr, $pat:expr, match { $($arm:tt)* }) => {
+        match $result {
+            ::core::result::Result::Ok(_) => {
+                ::core::panic!("expected an error matching `{}`, got Ok(_)", $pat)
+            }
+            ::core::result::Result::Err(err) => match err.try_get() {
+                $($arm)*
+                ::core::result::Result::Ok(other) => ::core::panic!(
+                    "expected an error matching `{}`, got {:?}",
+                    $pat,
+                    other
+                ),
+                ::core::result::Result::Err(err) => ::core::panic!(
+                    "expected an error matching `{}`, got an error of another type: {:?}",
+                    $pat,
+                    err
+                ),
+            },
+        }
+    };
+}
+
+/// Alias for `terror!`, for parity with `anyhow::format_err!`.
+#[macro_export]
+macro_rules! format_err {
+    ($msg:literal $(,)?) => {
+        $crate::terror!($msg)
+    };
     ($err:expr $(,)?) => {
-        $crate::TError::from_anyhow($crate::anyhow::anyhow!($err))
+        $crate::terror!($err)
     };
     ($fmt:expr, $($arg:tt)*) => {
-        $crate::TError::from_anyhow($crate::anyhow::anyhow!($fmt, $($arg)*))
+        $crate::terror!($fmt, $($arg)*)
     };
 }
 
@@ -24,11 +302,109 @@ macro_rules! bail {
     };
 }
 
+/// Return early with a `TError` with the given primary error, and optionally
+/// a formatted context message on top of it.
+///
+/// This is the same as `return Err(terror_typed!(...))`.
+///
+/// ```ignore
+/// bail_with!(MyError::NotFound, "id {}", id);
+/// ```
+#[macro_export]
+macro_rules! bail_with {
+    ($err:expr $(,)?) => {
+        return Err($crate::terror_typed!($err))
+    };
+    ($err:expr, $($arg:tt)+) => {
+        return Err($crate::terror_typed!($err, $($arg)+))
+    };
+}
+
+#[macro_export]
+macro_rules! ensure {
+    ($cond:expr $(,)?) => {
+        if !$cond {
+            return Err($crate::terror!(concat!("Condition failed: `", stringify!($cond), "`")));
+        }
+    };
+    ($cond:expr, $msg:literal $(,)?) => {
+        if !$cond {
+            return Err($crate::terror!($msg));
+        }
+    };
+    ($cond:expr, $err:expr $(,)?) => {
+        if !$cond {
+            return Err($crate::terror!($err));
+        }
+    };
+    ($cond:expr, $fmt:expr, $($arg:tt)*) => {
+        if !$cond {
+            return Err($crate::terror!($fmt, $($arg)*));
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! ensure_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                if !(*left_val == *right_val) {
+                    return Err($crate::terror!(
+                        "Condition failed: `{} == {}` (left: `{:?}`, right: `{:?}`)",
+                        stringify!($left),
+                        stringify!($right),
+                        left_val,
+                        right_val
+                    ));
+                }
+            }
+        }
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                if !(*left_val == *right_val) {
+                    return Err($crate::terror!($($arg)+));
+                }
+            }
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! ensure_ne {
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                if *left_val == *right_val {
+                    return Err($crate::terror!(
+                        "Condition failed: `{} != {}` (left: `{:?}`, right: `{:?}`)",
+                        stringify!($left),
+                        stringify!($right),
+                        left_val,
+                        right_val
+                    ));
+                }
+            }
+        }
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                if *left_val == *right_val {
+                    return Err($crate::terror!($($arg)+));
+                }
+            }
+        }
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use assert_matches::assert_matches;
-
-    use crate::{bail, terror};
+    use std::format;
+    use std::string::{String, ToString};
 
     #[derive(Debug, thiserror::Error)]
     enum MyError {
@@ -46,6 +422,31 @@ mod tests {
         bail!(MyError::Two);
     }
 
+    fn do_bail_with(id: u32) -> crate::Result<(), MyError> {
+        if id == 0 {
+            bail_with!(MyError::One);
+        }
+        bail_with!(MyError::Two, "id {}", id);
+    }
+
+    fn do_ensure(value: u32) -> crate::Result<u32, MyError> {
+        ensure!(value > 0);
+        ensure!(value < 10, MyError::Two);
+        ensure!(value != 5, "value must not be {}", value);
+        Ok(value)
+    }
+
+    fn do_ensure_eq(a: u32, b: u32) -> crate::Result<(), MyError> {
+        ensure_eq!(a, b);
+        Ok(())
+    }
+
+    fn do_ensure_ne(a: u32, b: u32) -> crate::Result<(), MyError> {
+        ensure_ne!(a, b, MyError::One);
+        ensure_ne!(a + 1, b, "off by {}", 1);
+        Ok(())
+    }
+
     fn do_terror() -> crate::Result<(), String> {
         Err(terror!("fake error"))
     }
@@ -60,6 +461,53 @@ mod tests {
         assert_matches!(e.get_ref(), Some(&MyError::Two));
     }
 
+    #[test]
+    fn test_bail_with_macro() {
+        let e = do_bail_with(0).unwrap_err();
+        assert_matches!(e.get_ref(), Some(&MyError::One));
+        assert_eq!(e.to_string(), "something went wrong");
+
+        let e = do_bail_with(7).unwrap_err();
+        assert_matches!(e.get_ref(), Some(&MyError::Two));
+        assert_eq!(format!("{e:#}"), "id 7: something else");
+    }
+
+    #[test]
+    fn test_ensure_macro() {
+        assert_eq!(do_ensure(1).unwrap(), 1);
+        assert_eq!(
+            do_ensure(0).unwrap_err().to_string(),
+            "Condition failed: `value > 0`"
+        );
+        assert_matches!(do_ensure(10).unwrap_err().get_ref(), Some(&MyError::Two));
+        assert_eq!(do_ensure(5).unwrap_err().to_string(), "value must not be 5");
+    }
+
+    #[test]
+    fn test_ensure_eq_macro() {
+        assert!(do_ensure_eq(1, 1).is_ok());
+        assert_eq!(
+            do_ensure_eq(1, 2).unwrap_err().to_string(),
+            "Condition failed: `a == b` (left: `1`, right: `2`)"
+        );
+
+        assert!(do_ensure_ne(1, 3).is_ok());
+        assert_matches!(
+            do_ensure_ne(1, 1).unwrap_err().get_ref(),
+            Some(&MyError::One)
+        );
+        assert_eq!(do_ensure_ne(1, 2).unwrap_err().to_string(), "off by 1");
+    }
+
+    #[test]
+    fn test_format_err_macro() {
+        let e: crate::TError<MyError> = format_err!("value: {}", 42);
+        assert_eq!(e.to_string(), "value: 42");
+
+        let e: crate::TError<MyError> = format_err!(MyError::One);
+        assert_matches!(e.get_ref(), Some(&MyError::One));
+    }
+
     #[test]
     fn test_terror_macro() {
         let a = do_terror();
@@ -69,4 +517,189 @@ mod tests {
         let e: crate::TError<MyError> = terror!(MyError::One);
         assert_matches!(e.get_ref(), Some(&MyError::One));
     }
+
+    #[test]
+    fn test_terror_macro_flattens_terror() {
+        let inner = crate::TError::<MyError>::new(MyError::Two).context("inner");
+        let e: crate::TError<MyError> = terror!(inner);
+        assert_eq!(format!("{e:#}"), "inner: something else");
+        assert_matches!(e.get_ref(), Some(&MyError::Two));
+
+        let e: crate::TError<()> = format_err!(e);
+        assert!(e.is::<MyError>());
+    }
+
+    #[test]
+    fn test_terror_typed_macro() {
+        let e = terror_typed!(MyError::One);
+        assert_matches!(e.get_ref(), Some(&MyError::One));
+
+        let e = terror_typed!(MyError::Two, "failed to load {}", "config");
+        assert_matches!(e.get_ref(), Some(&MyError::Two));
+        assert_eq!(format!("{e:#}"), "failed to load config: something else");
+    }
+
+    #[test]
+    fn test_terror_macro_source() {
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+        let e: crate::TError<MyError> = terror!("failed to load config"; source = io);
+        assert_eq!(e.to_string(), "failed to load config");
+        assert_eq!(e.source().unwrap().to_string(), "missing");
+        assert!(e.find_ref::<std::io::Error>().is_some());
+
+        let name = "config";
+        let e: crate::TError<MyError> = terror!("failed to load {}", name; source = MyError::One);
+        assert_eq!(
+            format!("{e:#}"),
+            "failed to load config: something went wrong"
+        );
+        assert_matches!(e.get_ref(), Some(&MyError::One));
+    }
+
+    #[test]
+    fn test_is_variant_macro() {
+        #[derive(Debug, thiserror::Error)]
+        enum FieldError {
+            #[error("missing {0}")]
+            Missing(&'static str),
+            #[error("invalid")]
+            Invalid { code: u32 },
+        }
+
+        let e = terror_typed!(MyError::One, "outer");
+        assert!(is_variant!(e, MyError::One));
+        assert!(!is_variant!(e, MyError::Two));
+
+        let e = crate::TError::new(FieldError::Missing("name"));
+        assert!(is_variant!(e, FieldError::Missing(..)));
+        assert!(is_variant!(e, FieldError::Missing(name) if *name == "name"));
+        assert!(!is_variant!(e, FieldError::Invalid { .. }));
+
+        let e = crate::TError::new(FieldError::Invalid { code: 3 });
+        assert!(is_variant!(
+            e,
+            FieldError::Missing(_) | FieldError::Invalid { .. }
+        ));
+
+        let e: crate::TError<FieldError> = terror!("other");
+        assert!(!is_variant!(e, FieldError::Missing(..)));
+    }
+
+    #[test]
+    fn test_define_error_macro() {
+        define_error! {
+            /// A generated error.
+            enum DefinedError {
+                #[error("not found: {0}")]
+                NotFound(String),
+                #[error("io error: {0}")]
+                Io(#[from] std::io::Error)
+            }
+            catch_all = Other;
+        }
+
+        fn find(name: &str) -> crate::Result<(), DefinedError> {
+            use crate::IntoTError;
+
+            match name {
+                "missing" => Err(crate::TError::new(DefinedError::NotFound(name.into()))),
+                "io" => Err(std::io::Error::new(std::io::ErrorKind::NotFound, "disk")).terror(),
+                _ => Err(terror!("unexpected: {}", name)),
+            }
+        }
+
+        assert_matches!(find("missing").unwrap_err().get(), DefinedError::NotFound(n) if n == "missing");
+        assert_matches!(find("io").unwrap_err().get(), DefinedError::Io(_));
+        match find("other").unwrap_err().get() {
+            DefinedError::Other(e) => assert_eq!(e.to_string(), "unexpected: other"),
+            e => panic!("unexpected error: {e:?}"),
+        }
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_expect_terror_macro() {
+        #[derive(Debug, thiserror::Error)]
+        enum UserError {
+            #[error("user {0} not found")]
+            NotFound(u32),
+            #[error("forbidden")]
+            Forbidden,
+        }
+
+        fn find_user(id: u32) -> crate::Result<(), UserError> {
+            match id {
+                0 => Ok(()),
+                1 => Err(crate::TError::new(UserError::Forbidden)),
+                2 => Err(terror!("unexpected")),
+                _ => Err(crate::TError::new(UserError::NotFound(id)).context("lookup")),
+            }
+        }
+
+        let id = expect_terror!(find_user(42), UserError::NotFound(id) => id);
+        assert_eq!(id, 42);
+        let err = expect_terror!(find_user(1), UserError::Forbidden);
+        assert_matches!(err, UserError::Forbidden);
+        expect_terror!(find_user(7), UserError::NotFound(id) if id == 7);
+
+        let panic = |f: fn()| {
+            let payload = std::panic::catch_unwind(f).unwrap_err();
+            match payload.downcast_ref::<&str>() {
+                Some(message) => message.to_string(),
+                None => payload.downcast_ref::<String>().unwrap().clone(),
+            }
+        };
+        assert_eq!(
+            panic(|| {
+                expect_terror!(find_user(42), UserError::Forbidden);
+            }),
+            "expected an error matching `UserError::Forbidden`, got NotFound(42)"
+        );
+        assert_eq!(
+            panic(|| {
+                expect_terror!(find_user(0), UserError::Forbidden);
+            }),
+            "expected an error matching `UserError::Forbidden`, got Ok(_)"
+        );
+        assert!(panic(|| {
+            expect_terror!(find_user(2), UserError::Forbidden);
+        })
+        .contains("got an error of another type: unexpected"));
+    }
+
+    #[test]
+    fn test_match_terror_macro() {
+        fn describe(err: crate::TError<MyError>) -> String {
+            match_terror!(err, {
+                MyError => |e| format!("my error: {e}"),
+                std::num::ParseIntError => |e| format!("parse error: {e}"),
+                _ => |err| format!("other error: {}", err.into_anyhow()),
+            })
+        }
+
+        let err = terror_typed!(MyError::One, "outer");
+        assert_eq!(describe(err), "my error: something went wrong");
+
+        let err = crate::TError::from("x".parse::<u32>().unwrap_err());
+        assert_eq!(describe(err), "parse error: invalid digit found in string");
+
+        let err = terror!("unknown");
+        assert_eq!(describe(err), "other error: unknown");
+    }
+
+    #[test]
+    fn test_location() {
+        let (e, line): (crate::TError<MyError>, _) = (terror!("located"), line!());
+        let e = e.context("more context");
+        let bailed = do_bail2().unwrap_err();
+        if cfg!(feature = "location") {
+            assert_eq!(e.location().unwrap().line(), line);
+            assert_eq!(e.location().unwrap().file(), file!());
+            assert_eq!(bailed.location().unwrap().file(), file!());
+            assert!(bailed.location().unwrap().line() < line);
+        } else {
+            assert!(e.location().is_none());
+            assert!(bailed.location().is_none());
+        }
+    }
 }
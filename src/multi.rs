@@ -0,0 +1,67 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error as StdError;
+use core::fmt::Display;
+
+/// A collection of errors that occurred together.
+///
+/// This is created by `TError::combine()` and is useful for reporting
+/// several failures at once, such as when validating many items.
+/// Each error retains its own context chain and notes.
+#[derive(Debug)]
+pub struct MultiError {
+    errors: Vec<anyhow::Error>,
+    /// The notes of each error, displayed after it. See `TError::note()`.
+    notes: Vec<Vec<String>>,
+}
+
+impl MultiError {
+    pub(crate) fn new(errors: Vec<anyhow::Error>, notes: Vec<Vec<String>>) -> Self {
+        debug_assert_eq!(errors.len(), notes.len());
+        Self { errors, notes }
+    }
+
+    /// The individual errors, in the order they were combined.
+    pub fn errors(&self) -> &[anyhow::Error] {
+        &self.errors
+    }
+
+    pub(crate) fn errors_mut(&mut self) -> &mut [anyhow::Error] {
+        &mut self.errors
+    }
+
+    /// The notes of the error at `index`, in the order they were added.
+    pub fn notes(&self, index: usize) -> impl Iterator<Item = &str> + '_ {
+        self.notes
+            .get(index)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+
+    /// Consume this error and return the individual errors.
+    ///
+    /// The notes of each error are discarded.
+    pub fn into_errors(self) -> Vec<anyhow::Error> {
+        self.errors
+    }
+
+    pub(crate) fn into_parts(self) -> (Vec<anyhow::Error>, Vec<Vec<String>>) {
+        (self.errors, self.notes)
+    }
+}
+
+impl Display for MultiError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} errors occurred:", self.errors.len())?;
+        for (error, notes) in self.errors.iter().zip(&self.notes) {
+            write!(f, "\n- {error:#}")?;
+            for note in notes {
+                write!(f, " {note}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl StdError for MultiError {}
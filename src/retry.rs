@@ -0,0 +1,81 @@
+//! Helpers for retrying operations that fail with transient errors.
+
+use core::fmt::{Debug, Display};
+
+use crate::{Result, Retryable};
+
+/// Call `f` until it succeeds, retrying up to `max` times while the error
+/// is transient.
+///
+/// The operation is attempted at most `max + 1` times. If the error is not
+/// transient (see `TError::is_transient()`), it is returned immediately.
+/// Otherwise the last error is returned once all retries are exhausted.
+///
+/// ```ignore
+/// let body = typederror::retry(3, || fetch(url))?;
+/// ```
+pub fn retry<F, T, E>(max: usize, mut f: F) -> Result<T, E>
+where
+    F: FnMut() -> Result<T, E>,
+    E: Retryable + Debug + Display + Send + Sync + 'static,
+{
+    let mut retries = 0;
+    loop {
+        match f() {
+            Err(err) if retries < max && err.is_transient() => retries += 1,
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TError;
+
+    #[derive(Debug, thiserror::Error)]
+    enum NetError {
+        #[error("timed out")]
+        Timeout,
+        #[error("refused")]
+        Refused,
+    }
+
+    impl Retryable for NetError {
+        fn is_transient(&self) -> bool {
+            matches!(self, NetError::Timeout)
+        }
+    }
+
+    #[test]
+    fn test_retry() {
+        let mut attempts = 0;
+        let value = retry(3, || {
+            attempts += 1;
+            if attempts < 3 {
+                Err(TError::new(NetError::Timeout))
+            } else {
+                Ok(attempts)
+            }
+        });
+        assert_eq!(value.unwrap(), 3);
+
+        let mut attempts = 0;
+        let err = retry(2, || -> Result<(), NetError> {
+            attempts += 1;
+            Err(TError::new(NetError::Timeout))
+        })
+        .unwrap_err();
+        assert!(err.is_transient());
+        assert_eq!(attempts, 3);
+
+        let mut attempts = 0;
+        let err = retry(5, || -> Result<(), NetError> {
+            attempts += 1;
+            Err(TError::new(NetError::Refused).context("connect"))
+        })
+        .unwrap_err();
+        assert!(!err.is_transient());
+        assert_eq!(attempts, 1);
+    }
+}
@@ -0,0 +1,115 @@
+use alloc::string::ToString;
+use core::fmt::{Debug, Display};
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+use crate::TError;
+
+/// Serialize a `TError` as a structured error.
+///
+/// The output contains the top-level `message`, the `chain` of error messages
+/// (most recent first), and the primary `error` if one of type E is present.
+/// If the primary error is not present, the `error` field is omitted.
+impl<E> Serialize for TError<E>
+where
+    E: Serialize + Debug + Display + Send + Sync + 'static,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let primary = self.get_ref();
+        let len = if primary.is_some() { 3 } else { 2 };
+        let mut state = serializer.serialize_struct("TError", len)?;
+        state.serialize_field("message", &self.to_string())?;
+        let chain = self.chain_strings();
+        state.serialize_field("chain", &chain)?;
+        match primary {
+            Some(e) => state.serialize_field("error", e)?,
+            None => state.skip_field("error")?,
+        }
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl<E> TError<E>
+where
+    E: Serialize + Debug + Display + Send + Sync + 'static,
+{
+    /// Convert this error into a JSON value, for structured logging.
+    ///
+    /// This contains the same fields as the `Serialize` impl, along with the
+    /// `primary_type` name of E. The `error` field is omitted if the primary
+    /// error is not present, or if it fails to serialize.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut value = serde_json::json!({
+            "message": self.to_string(),
+            "chain": self.chain_strings(),
+            "primary_type": self.primary_type_name(),
+        });
+        if let Some(error) = self.get_ref().and_then(|e| serde_json::to_value(e).ok()) {
+            value["error"] = error;
+        }
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::string::String;
+
+    use crate::{Context, TError};
+
+    #[derive(Debug, serde::Serialize, thiserror::Error)]
+    enum MyError {
+        #[error("not found: {0}")]
+        NotFound(String),
+    }
+
+    #[test]
+    fn test_serialize() {
+        let err: TError<MyError> = Err::<(), _>(MyError::NotFound("a".into()))
+            .context("lookup failed")
+            .unwrap_err();
+        assert_eq!(
+            serde_json::to_value(&err).unwrap(),
+            serde_json::json!({
+                "message": "lookup failed",
+                "chain": ["lookup failed", "not found: a"],
+                "error": { "NotFound": "a" },
+            })
+        );
+
+        let err: TError<MyError> = TError::from_msg("other").context("outer");
+        assert_eq!(
+            serde_json::to_value(&err).unwrap(),
+            serde_json::json!({
+                "message": "outer",
+                "chain": ["outer", "other"],
+            })
+        );
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn test_to_json() {
+        let err = TError::new(MyError::NotFound("a".into())).context("lookup failed");
+        assert_eq!(
+            err.to_json(),
+            serde_json::json!({
+                "message": "lookup failed",
+                "chain": ["lookup failed", "not found: a"],
+                "primary_type": core::any::type_name::<MyError>(),
+                "error": { "NotFound": "a" },
+            })
+        );
+
+        let err: TError<MyError> = TError::from_msg("other");
+        assert_eq!(
+            err.to_json(),
+            serde_json::json!({
+                "message": "other",
+                "chain": ["other"],
+                "primary_type": core::any::type_name::<MyError>(),
+            })
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use crate::TError;
+
+impl<E> TError<E> {
+    /// Emit this error as a `tracing` event at the `ERROR` level.
+    ///
+    /// The event message is the top-level error message. The `error.chain`
+    /// field lists each error in the chain, most recent first, and the
+    /// `error.primary_type` field contains the type name of the primary error E.
+    pub fn trace(&self) {
+        let chain = self.chain_strings();
+        tracing::error!(
+            error.chain = ?chain,
+            error.primary_type = self.primary_type_name(),
+            "{}",
+            self
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fmt::Debug;
+    use std::sync::{Arc, Mutex};
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    use crate::{Context, TError};
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("not found")]
+    struct NotFound;
+
+    /// A subscriber that records the fields of each event.
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<HashMap<String, String>>>>);
+
+    #[derive(Default)]
+    struct Fields(HashMap<String, String>);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{value:?}"));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields::default();
+            event.record(&mut fields);
+            self.0.lock().unwrap().push(fields.0);
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn test_trace() {
+        let recorder = Recorder::default();
+        let err: TError<NotFound> = Err::<(), _>(NotFound).context("lookup failed").unwrap_err();
+        tracing::subscriber::with_default(recorder.clone(), || err.trace());
+
+        let events = recorder.0.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["message"], "lookup failed");
+        assert_eq!(
+            events[0]["error.chain"],
+            r#"["lookup failed", "not found"]"#
+        );
+        assert!(events[0]["error.primary_type"].ends_with("NotFound"));
+    }
+}
@@ -0,0 +1,23 @@
+//! Glob-importing both `typederror::anyhow` and the prelude must not make
+//! `bail!` ambiguous. This has to be tested from outside the crate, since
+//! `macro_rules!` scoping always resolves to the crate's own `bail!` inside it.
+#![deny(warnings)]
+
+use typederror::anyhow::*;
+use typederror::prelude::*;
+
+fn do_bail() -> TEResult<(), ()> {
+    bail!("prelude bail");
+}
+
+fn do_anyhow_bail() -> Result<(), Error> {
+    anyhow_bail!("anyhow bail");
+}
+
+#[test]
+fn test_glob_imports() {
+    let err: TError<()> = do_bail().unwrap_err();
+    assert_eq!(err.to_string(), "prelude bail");
+    assert_eq!(do_anyhow_bail().unwrap_err().to_string(), "anyhow bail");
+    let _: Error = anyhow!("still available");
+}
@@ -0,0 +1,121 @@
+//! Derive macros for `typederror`.
+//!
+//! These are re-exported from `typederror` when the `derive` feature is enabled,
+//! and should be used from there rather than depending on this crate directly.
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Error, Fields, Path};
+
+/// Derive `typederror::DefaultError` for an enum.
+///
+/// Annotate exactly one variant with `#[default_error]`. That variant must
+/// contain a single field that can be constructed from `anyhow::Error`,
+/// such as `anyhow::Error` itself or `Box<dyn Error + Send + Sync + 'static>`.
+///
+/// ```ignore
+/// #[derive(Debug, thiserror::Error, typederror::DefaultError)]
+/// enum MyError {
+///     #[error("IO error: {0}")]
+///     IoError(#[from] std::io::Error),
+///     #[error("{0}")]
+///     #[default_error]
+///     Misc(typederror::anyhow::Error),
+/// }
+/// ```
+///
+/// The generated code refers to the `typederror` crate as `::typederror`.
+/// If the dependency has been renamed, specify its path on the enum with
+/// `#[typederror(crate = path)]`:
+///
+/// ```ignore
+/// #[derive(Debug, thiserror::Error, te::DefaultError)]
+/// #[typederror(crate = te)]
+/// enum MyError {
+///     #[error("{0}")]
+///     #[default_error]
+///     Misc(te::anyhow::Error),
+/// }
+/// ```
+#[proc_macro_derive(DefaultError, attributes(default_error, typederror))]
+pub fn derive_default_error(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_default_error(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// The path to the `typederror` crate, from `#[typederror(crate = path)]`.
+fn crate_path(attrs: &[Attribute]) -> syn::Result<Path> {
+    let mut path = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("typederror")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("crate") {
+                path = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported typederror attribute, expected `crate`"))
+            }
+        })?;
+    }
+    Ok(path.unwrap_or_else(|| syn::parse_quote!(::typederror)))
+}
+
+fn expand_default_error(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "DefaultError can only be derived for enums",
+            ))
+        }
+    };
+
+    let mut annotated = data
+        .variants
+        .iter()
+        .filter(|v| v.attrs.iter().any(|a| a.path().is_ident("default_error")));
+    let variant = match (annotated.next(), annotated.next()) {
+        (Some(variant), None) => variant,
+        (None, _) => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "expected exactly one variant annotated with #[default_error]",
+            ))
+        }
+        (Some(_), Some(extra)) => {
+            return Err(Error::new_spanned(
+                &extra.ident,
+                "only one variant can be annotated with #[default_error]",
+            ))
+        }
+    };
+
+    let ident = &variant.ident;
+    let construct = match &variant.fields {
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            quote!(Self::#ident(::core::convert::From::from(err)))
+        }
+        Fields::Named(fields) if fields.named.len() == 1 => {
+            let field = fields.named[0].ident.as_ref();
+            quote!(Self::#ident { #field: ::core::convert::From::from(err) })
+        }
+        _ => {
+            return Err(Error::new_spanned(
+                ident,
+                "the #[default_error] variant must contain exactly one field",
+            ))
+        }
+    };
+
+    let krate = crate_path(&input.attrs)?;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #krate::DefaultError for #name #ty_generics #where_clause {
+            fn from_anyhow(err: #krate::anyhow::Error) -> Self {
+                #construct
+            }
+        }
+    })
+}
@@ -0,0 +1,62 @@
+use typederror::{Context, DefaultError, TError};
+
+#[derive(Debug, thiserror::Error, DefaultError)]
+enum MyError {
+    #[error("something went wrong")]
+    One,
+    #[error("{0}")]
+    #[default_error]
+    Misc(typederror::anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error, DefaultError)]
+enum BoxedError {
+    #[error("{source}")]
+    #[default_error]
+    Other {
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
+}
+
+fn fallible_fn() -> typederror::Result<(), MyError> {
+    std::fs::read_to_string("/invalid-dir-doesnt-exist").context("read failed")?;
+    Ok(())
+}
+
+#[test]
+fn test_derive_default_error() {
+    let err = TError::new(MyError::One);
+    assert!(matches!(err.get(), MyError::One));
+
+    let err = fallible_fn().unwrap_err();
+    match err.get() {
+        MyError::Misc(e) => assert_eq!(e.to_string(), "read failed"),
+        e => panic!("unexpected error: {e:?}"),
+    }
+}
+
+#[test]
+fn test_derive_default_error_named() {
+    let err: TError<BoxedError> = fallible_fn().unwrap_err().change_err();
+    let BoxedError::Other { source } = err.get();
+    assert_eq!(source.to_string(), "read failed");
+}
+
+mod renamed {
+    use ::typederror as te;
+
+    #[derive(Debug, thiserror::Error, te::DefaultError)]
+    #[typederror(crate = te)]
+    pub enum RenamedError {
+        #[error("{0}")]
+        #[default_error]
+        Misc(te::anyhow::Error),
+    }
+}
+
+#[test]
+fn test_derive_default_error_crate_path() {
+    let err: TError<renamed::RenamedError> = fallible_fn().unwrap_err().change_err();
+    let renamed::RenamedError::Misc(e) = err.get();
+    assert_eq!(e.to_string(), "read failed");
+}
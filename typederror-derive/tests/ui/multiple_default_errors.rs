@@ -0,0 +1,11 @@
+#[derive(Debug, thiserror::Error, typederror::DefaultError)]
+enum MyError {
+    #[error("{0}")]
+    #[default_error]
+    Misc(typederror::anyhow::Error),
+    #[error("{0}")]
+    #[default_error]
+    Other(typederror::anyhow::Error),
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+#[derive(Debug, thiserror::Error, typederror::DefaultError)]
+enum MyError {
+    #[error("{0}: {1}")]
+    #[default_error]
+    Misc(String, typederror::anyhow::Error),
+}
+
+fn main() {}
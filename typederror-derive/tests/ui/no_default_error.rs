@@ -0,0 +1,9 @@
+#[derive(Debug, thiserror::Error, typederror::DefaultError)]
+enum MyError {
+    #[error("something went wrong")]
+    One,
+    #[error("{0}")]
+    Misc(typederror::anyhow::Error),
+}
+
+fn main() {}
@@ -0,0 +1,5 @@
+#[derive(Debug, thiserror::Error, typederror::DefaultError)]
+#[error("{0}")]
+struct MyError(typederror::anyhow::Error);
+
+fn main() {}
@@ -0,0 +1,9 @@
+#[derive(Debug, thiserror::Error, typederror::DefaultError)]
+#[typederror(krate = typederror)]
+enum MyError {
+    #[error("{0}")]
+    #[default_error]
+    Misc(typederror::anyhow::Error),
+}
+
+fn main() {}